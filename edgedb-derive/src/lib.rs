@@ -9,6 +9,12 @@ use syn::{self, parse_macro_input};
 pub fn edgedb_queryable(input: TokenStream) -> TokenStream {
     let s = parse_macro_input!(input as syn::ItemStruct);
 
+    match is_transparent(&s.attrs) {
+        Ok(true) => return derive_transparent(s),
+        Ok(false) => {}
+        Err(e) => return e.to_compile_error().into(),
+    }
+
     let name = s.ident;
     let (impl_generics, ty_generics, _) = s.generics.split_for_impl();
     let fields = match s.fields {
@@ -25,7 +31,8 @@ pub fn edgedb_queryable(input: TokenStream) -> TokenStream {
     let fieldtype = fields.named.iter()
         .map(|f| f.ty.clone()).collect::<Vec<_>>();
     let fieldstr = fieldname.iter()
-        .map(|s| syn::LitStr::new(&s.to_string(), s.span()));
+        .map(|s| syn::LitStr::new(&s.to_string(), s.span()))
+        .collect::<Vec<_>>();
     let has_id = fieldname.iter().find(|x| x.to_string() == "id").is_some();
     let has_type_id = fieldname.iter().find(|x| x.to_string() == "__tid__").is_some();
     let implicit_fields =
@@ -33,6 +40,13 @@ pub fn edgedb_queryable(input: TokenStream) -> TokenStream {
         if has_type_id { 0 } else { 1 };
     let nfields = fields.named.len()+implicit_fields;
     let fieldno = implicit_fields..nfields;
+    let mut implicit_names = Vec::new();
+    if !has_type_id {
+        implicit_names.push("__tid__");
+    }
+    if !has_id {
+        implicit_names.push("id");
+    }
     let typeid_block = if has_type_id {
         None
     } else {
@@ -131,7 +145,7 @@ pub fn edgedb_queryable(input: TokenStream) -> TokenStream {
                 let shape = match desc {
                     ObjectShape(shape) => shape,
                     _ => {
-                        return Err(ctx.wrong_type(desc, "str"))
+                        return Err(ctx.wrong_type(desc, "object"))
                     }
                 };
 
@@ -154,8 +168,87 @@ pub fn edgedb_queryable(input: TokenStream) -> TokenStream {
                 Ok(())
             }
         }
+        impl #impl_generics ::edgedb_protocol::queryable::ShapeFields
+            for #name #ty_generics {
+            fn field_names() -> &'static [&'static str] {
+                &[
+                    #(#implicit_names,)*
+                    #(#fieldstr,)*
+                ]
+            }
+        }
     };
 
     // Hand the output tokens back to the compiler
     TokenStream::from(expanded)
 }
+
+fn is_transparent(attrs: &[syn::Attribute]) -> syn::Result<bool> {
+    let mut transparent = false;
+    for attr in attrs {
+        if !attr.path.is_ident("edgedb") {
+            continue;
+        }
+        let list = match attr.parse_meta()? {
+            syn::Meta::List(list) => list,
+            meta => {
+                return Err(syn::Error::new_spanned(meta,
+                    "expected `#[edgedb(...)]`"));
+            }
+        };
+        for item in list.nested {
+            match item {
+                syn::NestedMeta::Meta(syn::Meta::Path(ref path))
+                    if path.is_ident("transparent")
+                => {
+                    transparent = true;
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(item,
+                        "unknown edgedb attribute"));
+                }
+            }
+        }
+    }
+    Ok(transparent)
+}
+
+/// Newtype decoded exactly like its only field
+fn derive_transparent(s: syn::ItemStruct) -> TokenStream {
+    let name = s.ident;
+    let (impl_generics, ty_generics, _) = s.generics.split_for_impl();
+    if s.fields.iter().count() != 1 {
+        return syn::Error::new_spanned(
+            s.fields, "transparent struct must have exactly one field")
+            .to_compile_error()
+            .into();
+    }
+    let field = s.fields.iter().next().unwrap();
+    let fieldtype = &field.ty;
+    let construct = match &field.ident {
+        Some(fieldname) => quote! { #name { #fieldname: value } },
+        None => quote! { #name(value) },
+    };
+    let expanded = quote! {
+        impl #impl_generics ::edgedb_protocol::queryable::Queryable
+            for #name #ty_generics {
+            fn decode_raw(buf: &mut ::std::io::Cursor<::bytes::Bytes>)
+                -> Result<Self, ::edgedb_protocol::errors::DecodeError>
+            {
+                let value =
+                    <#fieldtype as ::edgedb_protocol::queryable::Queryable>
+                    ::decode_raw(buf)?;
+                Ok(#construct)
+            }
+            fn check_descriptor(
+                ctx: &::edgedb_protocol::queryable::DescriptorContext,
+                type_pos: ::edgedb_protocol::descriptors::TypePos)
+                -> Result<(), ::edgedb_protocol::queryable::DescriptorMismatch>
+            {
+                <#fieldtype as ::edgedb_protocol::queryable::Queryable>
+                    ::check_descriptor(ctx, type_pos)
+            }
+        }
+    };
+    TokenStream::from(expanded)
+}
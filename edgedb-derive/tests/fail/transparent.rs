@@ -0,0 +1,8 @@
+use edgedb_derive::Queryable;
+
+#[derive(Queryable)]
+#[edgedb(transparent)]
+struct Pair(String, String);
+
+fn main() {
+}
@@ -0,0 +1,43 @@
+//! Decodes random type descriptors and random data with the codec built
+//!
+//! Input is a big endian u16 length of the descriptors followed by
+//! descriptors themselves, the rest is the data to decode. Root type is
+//! the last descriptor, as the server always sends it last.
+#![no_main]
+use std::convert::TryInto;
+use std::io::Cursor;
+
+use bytes::{Bytes, Buf};
+use libfuzzer_sys::fuzz_target;
+
+use edgedb_protocol::codec::build_codec;
+use edgedb_protocol::descriptors::{Descriptor, TypePos};
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 2 {
+        return;
+    }
+    let len = u16::from_be_bytes([data[0], data[1]]) as usize;
+    if data.len() < 2 + len {
+        return;
+    }
+    let mut cur = Cursor::new(Bytes::copy_from_slice(&data[2..2+len]));
+    let mut descriptors = Vec::new();
+    while cur.bytes() != b"" {
+        match Descriptor::decode(&mut cur) {
+            Ok(Descriptor::TypeAnnotation(_)) => {}
+            Ok(item) => descriptors.push(item),
+            Err(_) => return,
+        }
+    }
+    let root_pos = match descriptors.len().checked_sub(1) {
+        Some(pos) => TypePos(pos.try_into().unwrap()),
+        None => return,
+    };
+    let codec = match build_codec(Some(root_pos), &descriptors) {
+        Ok(codec) => codec,
+        Err(_) => return,
+    };
+    let value = Bytes::copy_from_slice(&data[2+len..]);
+    codec.decode_value(&mut Cursor::new(value)).ok();
+});
@@ -0,0 +1,91 @@
+//! Encodes a random value and checks that it decodes back unchanged
+#![no_main]
+use std::convert::TryInto;
+use std::io::Cursor;
+
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+use uuid::Uuid;
+
+use edgedb_protocol::codec::{self, build_codec};
+use edgedb_protocol::descriptors::{Descriptor, TypePos};
+use edgedb_protocol::descriptors::{BaseScalarTypeDescriptor, SetDescriptor};
+use edgedb_protocol::descriptors::{ArrayTypeDescriptor, TupleTypeDescriptor};
+use edgedb_protocol::value::{Value, HashableValue};
+
+fn push(descriptors: &mut Vec<Descriptor>, desc: Descriptor) -> TypePos {
+    descriptors.push(desc);
+    TypePos((descriptors.len() - 1).try_into().unwrap())
+}
+
+fn scalar(descriptors: &mut Vec<Descriptor>, id: Uuid) -> TypePos {
+    push(descriptors, Descriptor::BaseScalar(BaseScalarTypeDescriptor { id }))
+}
+
+// Descriptor ids are only used to find the root and for caching, so the
+// same made up id is used for all the collections
+fn describe(descriptors: &mut Vec<Descriptor>, value: &Value) -> TypePos {
+    use Value as V;
+    let id = Uuid::from_u128(0x1);
+    match value {
+        V::Uuid(_) => scalar(descriptors, codec::STD_UUID),
+        V::Str(_) => scalar(descriptors, codec::STD_STR),
+        V::Bytes(_) => scalar(descriptors, codec::STD_BYTES),
+        V::Int16(_) => scalar(descriptors, codec::STD_INT16),
+        V::Int32(_) => scalar(descriptors, codec::STD_INT32),
+        V::Int64(_) => scalar(descriptors, codec::STD_INT64),
+        V::Float32(_) => scalar(descriptors, codec::STD_FLOAT32),
+        V::Float64(_) => scalar(descriptors, codec::STD_FLOAT64),
+        V::BigInt(_) => scalar(descriptors, codec::STD_BIGINT),
+        V::Decimal(_) => scalar(descriptors, codec::STD_DECIMAL),
+        V::Bool(_) => scalar(descriptors, codec::STD_BOOL),
+        V::Datetime(_) => scalar(descriptors, codec::STD_DATETIME),
+        V::LocalDatetime(_)
+        => scalar(descriptors, codec::CAL_LOCAL_DATETIME),
+        V::LocalDate(_) => scalar(descriptors, codec::CAL_LOCAL_DATE),
+        V::LocalTime(_) => scalar(descriptors, codec::CAL_LOCAL_TIME),
+        V::Duration(_) => scalar(descriptors, codec::STD_DURATION),
+        V::Json(_) => scalar(descriptors, codec::STD_JSON),
+        V::Array(items) => {
+            let type_pos = element(descriptors, items);
+            push(descriptors, Descriptor::Array(ArrayTypeDescriptor {
+                id,
+                type_pos,
+                dimensions: vec![None],
+            }))
+        }
+        V::Set(items) => {
+            let type_pos = element(descriptors, items);
+            push(descriptors, Descriptor::Set(SetDescriptor { id, type_pos }))
+        }
+        V::Tuple(items) => {
+            let element_types = items.iter()
+                .map(|item| describe(descriptors, item))
+                .collect();
+            push(descriptors, Descriptor::Tuple(TupleTypeDescriptor {
+                id,
+                element_types,
+            }))
+        }
+        _ => unreachable!("value is not generated: {:?}", value),
+    }
+}
+
+fn element(descriptors: &mut Vec<Descriptor>, items: &[Value]) -> TypePos {
+    match items.first() {
+        Some(item) => describe(descriptors, item),
+        None => scalar(descriptors, codec::STD_INT64),
+    }
+}
+
+fuzz_target!(|value: Value| {
+    let mut descriptors = Vec::new();
+    let root_pos = describe(&mut descriptors, &value);
+    let codec = build_codec(Some(root_pos), &descriptors)
+        .expect("codec for generated value");
+    let mut buf = BytesMut::new();
+    codec.encode(&mut buf, &value).expect("generated value is encodable");
+    let decoded = codec.decode_value(&mut Cursor::new(buf.freeze()))
+        .expect("encoded value is decodable");
+    assert_eq!(HashableValue(decoded), HashableValue(value));
+});
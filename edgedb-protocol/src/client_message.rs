@@ -5,10 +5,15 @@ use std::u16;
 
 use bytes::{Bytes, BytesMut, BufMut, Buf};
 use snafu::{OptionExt, ensure};
+use uuid::Uuid;
 
 use crate::encoding::{Encode, Decode, Headers, encode};
 use crate::errors::{self, EncodeError, DecodeError};
-pub use crate::common::Cardinality;
+pub use crate::common::{Cardinality, Capabilities};
+
+/// Header of `Prepare` limiting the number of rows returned by a query
+/// that has no explicit `LIMIT` (before protocol 1.0)
+pub const HEADER_IMPLICIT_LIMIT: u16 = 0xFF01;
 
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -19,6 +24,7 @@ pub enum ClientMessage {
     Prepare(Prepare),
     DescribeStatement(DescribeStatement),
     Execute(Execute),
+    Execute1(Execute1),
     UnknownMessage(u8, Bytes),
     AuthenticationSaslInitialResponse(SaslInitialResponse),
     AuthenticationSaslResponse(SaslResponse),
@@ -79,6 +85,24 @@ pub struct Execute {
     pub arguments: Bytes,
 }
 
+/// Combined prepare and execute message of protocol 1.0 and later
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Execute1 {
+    pub headers: Headers,
+    pub allowed_capabilities: Capabilities,
+    pub compilation_flags: u64,
+    /// Zero means no limit
+    pub implicit_limit: u64,
+    pub output_format: IoFormat,
+    pub expected_cardinality: Cardinality,
+    pub command_text: String,
+    pub state_typedesc_id: Uuid,
+    pub state_data: Bytes,
+    pub input_typedesc_id: Uuid,
+    pub output_typedesc_id: Uuid,
+    pub arguments: Bytes,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Dump {
     pub headers: Headers,
@@ -101,7 +125,7 @@ pub enum DescribeAspect {
     DataDescription = 0x54,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum IoFormat {
     Binary = 0x62,
     Json = 0x6a,
@@ -109,6 +133,26 @@ pub enum IoFormat {
 }
 
 
+impl Prepare {
+    /// Sets the implicit limit header, zero means no limit
+    ///
+    /// Since protocol 1.0 the limit is the `implicit_limit` field of
+    /// `Execute1` instead.
+    pub fn set_implicit_limit(&mut self, limit: u64) {
+        if limit == 0 {
+            self.headers.remove(&HEADER_IMPLICIT_LIMIT);
+        } else {
+            self.headers.insert(HEADER_IMPLICIT_LIMIT,
+                                Bytes::from(limit.to_string()));
+        }
+    }
+    /// Returns the implicit limit if it's set and valid
+    pub fn implicit_limit(&self) -> Option<u64> {
+        let value = self.headers.get(&HEADER_IMPLICIT_LIMIT)?;
+        std::str::from_utf8(value).ok()?.parse().ok()
+    }
+}
+
 struct Empty;
 impl ClientMessage {
     pub fn encode(&self, buf: &mut BytesMut) -> Result<(), EncodeError> {
@@ -121,6 +165,7 @@ impl ClientMessage {
             Prepare(h) => encode(buf, 0x50, h),
             DescribeStatement(h) => encode(buf, 0x44, h),
             Execute(h) => encode(buf, 0x45, h),
+            Execute1(h) => encode(buf, 0x4f, h),
             Dump(h) => encode(buf, 0x3e, h),
             Restore(h) => encode(buf, 0x3c, h),
             RestoreBlock(h) => encode(buf, 0x3d, h),
@@ -151,6 +196,7 @@ impl ClientMessage {
             0x51 => ExecuteScript::decode(&mut data).map(M::ExecuteScript),
             0x50 => Prepare::decode(&mut data).map(M::Prepare),
             0x45 => Execute::decode(&mut data).map(M::Execute),
+            0x4f => Execute1::decode(&mut data).map(M::Execute1),
             0x3e => Dump::decode(&mut data).map(M::Dump),
             0x3c => Restore::decode(&mut data).map(M::Restore),
             0x3d => RestoreBlock::decode(&mut data).map(M::RestoreBlock),
@@ -427,6 +473,82 @@ impl Decode for Execute {
     }
 }
 
+impl Encode for Execute1 {
+    fn encode(&self, buf: &mut BytesMut)
+        -> Result<(), EncodeError>
+    {
+        buf.reserve(2);
+        buf.put_u16(u16::try_from(self.headers.len()).ok()
+            .context(errors::TooManyHeaders)?);
+        for (&name, value) in &self.headers {
+            buf.reserve(2);
+            buf.put_u16(name);
+            value.encode(buf)?;
+        }
+        buf.reserve(26);
+        buf.put_u64(self.allowed_capabilities.bits());
+        buf.put_u64(self.compilation_flags);
+        buf.put_u64(self.implicit_limit);
+        buf.put_u8(self.output_format as u8);
+        buf.put_u8(self.expected_cardinality as u8);
+        self.command_text.encode(buf)?;
+        self.state_typedesc_id.encode(buf)?;
+        self.state_data.encode(buf)?;
+        self.input_typedesc_id.encode(buf)?;
+        self.output_typedesc_id.encode(buf)?;
+        self.arguments.encode(buf)?;
+        Ok(())
+    }
+}
+
+impl Decode for Execute1 {
+    fn decode(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
+        ensure!(buf.remaining() >= 2, errors::Underflow);
+        let num_headers = buf.get_u16();
+        let mut headers = HashMap::new();
+        for _ in 0..num_headers {
+            ensure!(buf.remaining() >= 4, errors::Underflow);
+            headers.insert(buf.get_u16(), Bytes::decode(buf)?);
+        }
+        ensure!(buf.remaining() >= 26, errors::Underflow);
+        let allowed_capabilities =
+            Capabilities::from_bits_truncate(buf.get_u64());
+        let compilation_flags = buf.get_u64();
+        let implicit_limit = buf.get_u64();
+        let output_format = match buf.get_u8() {
+            0x62 => IoFormat::Binary,
+            0x6a => IoFormat::Json,
+            0x4a => IoFormat::JsonElements,
+            c => errors::InvalidIoFormat { io_format: c }.fail()?,
+        };
+        let expected_cardinality = match buf.get_u8() {
+            0x6f => Cardinality::One,
+            0x6d => Cardinality::Many,
+            c => errors::InvalidCardinality { cardinality: c }.fail()?,
+        };
+        let command_text = String::decode(buf)?;
+        let state_typedesc_id = Uuid::decode(buf)?;
+        let state_data = Bytes::decode(buf)?;
+        let input_typedesc_id = Uuid::decode(buf)?;
+        let output_typedesc_id = Uuid::decode(buf)?;
+        let arguments = Bytes::decode(buf)?;
+        Ok(Execute1 {
+            headers,
+            allowed_capabilities,
+            compilation_flags,
+            implicit_limit,
+            output_format,
+            expected_cardinality,
+            command_text,
+            state_typedesc_id,
+            state_data,
+            input_typedesc_id,
+            output_typedesc_id,
+            arguments,
+        })
+    }
+}
+
 impl Encode for Dump {
     fn encode(&self, buf: &mut BytesMut)
         -> Result<(), EncodeError>
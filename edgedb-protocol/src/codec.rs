@@ -1,22 +1,28 @@
 use std::any::type_name;
+use std::borrow::Borrow;
+use std::cell::Cell;
 use std::convert::{TryInto, TryFrom};
 use std::fmt;
 use std::str;
-use std::time::{UNIX_EPOCH, SystemTime};
 use std::io::Cursor;
 use std::sync::Arc;
 use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 
 use bytes::{Bytes as Buf, Buf as _, BytesMut, BufMut};
 use uuid::Uuid as UuidVal;
 use snafu::{ensure, OptionExt, ResultExt};
 
-use crate::descriptors::{self, Descriptor, TypePos};
+use crate::descriptors::{self, Descriptor, TypePos, ShapeCardinality};
+use crate::sealed::Sealed;
 use crate::errors::{self, CodecError, DecodeError, EncodeError};
 use crate::value::{self, Value};
 
+pub mod cache;
 pub mod raw;
+pub mod scratch;
+pub(crate) mod validate;
 
 pub const STD_UUID: UuidVal = UuidVal::from_u128(0x100);
 pub const STD_STR: UuidVal = UuidVal::from_u128(0x101);
@@ -37,38 +43,58 @@ pub const STD_JSON: UuidVal = UuidVal::from_u128(0x10f);
 pub const STD_BIGINT: UuidVal = UuidVal::from_u128(0x110);
 
 
-pub trait Codec: fmt::Debug + Send + Sync + 'static {
+/// Codecs are built from type descriptors only, see `build_codec`
+pub trait Codec: Sealed + fmt::Debug + Send + Sync + 'static {
     fn decode(&self, buf: &mut Cursor<Buf>) -> Result<Value, DecodeError>;
     fn encode(&self, buf: &mut BytesMut, value: &Value)
         -> Result<(), EncodeError>;
+    /// Encodes an array or a set from elements produced by an iterator
+    ///
+    /// Codecs other than arrays and sets return `InvalidValue` error.
+    /// See also `encode_from_iter`.
+    fn encode_iter(&self, buf: &mut BytesMut,
+                   items: &mut dyn Iterator<Item=Value>)
+        -> Result<(), EncodeError>
+    {
+        let _ = (buf, items);
+        errors::InvalidValue {
+            value_type: "iterator",
+            codec: type_name::<Self>(),
+        }.fail()
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct EnumValue(Arc<str>);
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct ObjectShape(Arc<ObjectShapeInfo>);
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct NamedTupleShape(Arc<NamedTupleShapeInfo>);
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature="serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObjectShapeInfo {
     pub elements: Vec<ShapeElement>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature="serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShapeElement {
     pub flag_implicit: bool,
     pub flag_link_property: bool,
     pub flag_link: bool,
+    pub cardinality: Option<ShapeCardinality>,
     pub name: String,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature="serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NamedTupleShapeInfo {
     pub elements: Vec<TupleElement>,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature="serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TupleElement {
     pub name: String,
 }
@@ -113,10 +139,14 @@ pub struct LocalDate;
 pub struct LocalTime;
 
 #[derive(Debug)]
-pub struct Decimal;
+pub struct Decimal {
+    max_digits: usize,
+}
 
 #[derive(Debug)]
-pub struct BigInt;
+pub struct BigInt {
+    max_digits: usize,
+}
 
 #[derive(Debug)]
 pub struct Bool;
@@ -136,6 +166,7 @@ pub struct Object {
 #[derive(Debug)]
 pub struct Set {
     element: Arc<dyn Codec>,
+    order: SetOrder,
 }
 
 #[derive(Debug)]
@@ -175,9 +206,56 @@ pub struct Enum {
     members: HashSet<Arc<str>>,
 }
 
+/// Limits and options applied to the data decoded by codecs
+///
+/// Digits are counted as they are sent on the wire, i.e. in base 10000
+/// (each one holds four decimal digits).
+///
+/// Codecs encode and decode values recursively, so `max_depth` limits
+/// both the nesting of type descriptors and the stack used for each
+/// value. Descriptors nested deeper fail to build with `TooDeep` error.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    pub max_bigint_digits: usize,
+    pub max_decimal_digits: usize,
+    pub max_depth: usize,
+    pub set_order: SetOrder,
+}
+
+/// Order of elements in decoded `Value::Set`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOrder {
+    /// Elements are kept in the order sent by the server
+    ///
+    /// This order is unspecified for most queries and may differ between
+    /// runs of the same query.
+    Wire,
+    /// Elements are sorted by their binary representation
+    ///
+    /// The same set of elements is always decoded in the same order, but
+    /// the order is not the natural order of values (e.g. negative
+    /// integers are sorted after positive ones).
+    Sorted,
+}
+
 struct CodecBuilder<'a> {
     input: bool,
     descriptors: &'a [Descriptor],
+    limits: &'a Limits,
+    depth: Cell<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        // Enough for 131072 digits before and 16383 after the decimal
+        // point, which is the range of postgres numeric type
+        Limits {
+            max_bigint_digits: 32768,
+            max_decimal_digits: 36864,
+            max_depth: 64,
+            set_order: SetOrder::Wire,
+        }
+    }
 }
 
 impl ObjectShape {
@@ -186,6 +264,83 @@ impl ObjectShape {
     }
 }
 
+impl NamedTupleShape {
+    pub fn new(elements: Vec<TupleElement>) -> NamedTupleShape {
+        NamedTupleShape(Arc::new(NamedTupleShapeInfo { elements }))
+    }
+}
+
+// Shapes are usually shared between all values produced by a codec, so
+// comparing pointers first makes shape checks on encoding cheap
+impl PartialEq for ObjectShape {
+    fn eq(&self, other: &ObjectShape) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0 == other.0
+    }
+}
+
+impl Eq for ObjectShape {}
+
+impl PartialEq for NamedTupleShape {
+    fn eq(&self, other: &NamedTupleShape) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.0 == other.0
+    }
+}
+
+impl Eq for NamedTupleShape {}
+
+impl Hash for ObjectShape {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl Hash for NamedTupleShape {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+/// Serialized as `ObjectShapeInfo`, i.e. `{"elements": [...]}`
+#[cfg(feature="serde")]
+impl serde::Serialize for ObjectShape {
+    fn serialize<S: serde::Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        serde::Serialize::serialize(&*self.0, serializer)
+    }
+}
+
+#[cfg(feature="serde")]
+impl<'de> serde::Deserialize<'de> for ObjectShape {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D)
+        -> Result<ObjectShape, D::Error>
+    {
+        <ObjectShapeInfo as serde::Deserialize>::deserialize(deserializer)
+            .map(|info| ObjectShape(Arc::new(info)))
+    }
+}
+
+/// Serialized as `NamedTupleShapeInfo`, i.e. `{"elements": [...]}`
+#[cfg(feature="serde")]
+impl serde::Serialize for NamedTupleShape {
+    fn serialize<S: serde::Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        serde::Serialize::serialize(&*self.0, serializer)
+    }
+}
+
+#[cfg(feature="serde")]
+impl<'de> serde::Deserialize<'de> for NamedTupleShape {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D)
+        -> Result<NamedTupleShape, D::Error>
+    {
+        <NamedTupleShapeInfo as serde::Deserialize>::deserialize(
+                deserializer)
+            .map(|info| NamedTupleShape(Arc::new(info)))
+    }
+}
+
 impl Deref for ObjectShape {
     type Target = ObjectShapeInfo;
     fn deref(&self) -> &ObjectShapeInfo {
@@ -208,14 +363,42 @@ impl dyn Codec {
         ensure!(buf.bytes().len() == 0, errors::ExtraData);
         Ok(result)
     }
+    /// Encodes an array or a set without collecting elements into a `Vec`
+    ///
+    /// Each element is converted to `Value` and encoded right away, so
+    /// encoding a large argument (e.g. thousands of ids) only holds one
+    /// element at a time.
+    pub fn encode_from_iter<I>(&self, buf: &mut BytesMut, items: I)
+        -> Result<(), EncodeError>
+        where I: IntoIterator,
+              I::Item: Into<Value>,
+    {
+        self.encode_iter(buf, &mut items.into_iter().map(Into::into))
+    }
 }
 
 impl<'a> CodecBuilder<'a> {
+    fn new(input: bool, descriptors: &'a [Descriptor], limits: &'a Limits)
+        -> CodecBuilder<'a>
+    {
+        CodecBuilder { input, descriptors, limits, depth: Cell::new(0) }
+    }
     fn build(&self, pos: TypePos) -> Result<Arc<dyn Codec>, CodecError> {
+        let depth = self.depth.get();
+        ensure!(depth < self.limits.max_depth,
+                errors::TooDeep { max_depth: self.limits.max_depth });
+        self.depth.set(depth + 1);
+        let result = self.build_item(pos);
+        self.depth.set(depth);
+        result
+    }
+    fn build_item(&self, pos: TypePos) -> Result<Arc<dyn Codec>, CodecError> {
         use Descriptor as D;
         if let Some(item) = self.descriptors.get(pos.0 as usize) {
             match item {
-                D::BaseScalar(base) => scalar_codec(&base.id),
+                D::BaseScalar(base) => {
+                    scalar_codec_with_limits(&base.id, self.limits)
+                }
                 D::Set(d) => Ok(Arc::new(Set::build(d, self)?)),
                 D::ObjectShape(d) => Ok(Arc::new(Object::build(d, self)?)),
                 D::Scalar(d) => Ok(Arc::new(Scalar {
@@ -255,7 +438,14 @@ pub fn build_codec(root_pos: Option<TypePos>,
     descriptors: &[Descriptor])
     -> Result<Arc<dyn Codec>, CodecError>
 {
-    let dec = CodecBuilder { input: false, descriptors };
+    build_codec_with_limits(root_pos, descriptors, &Limits::default())
+}
+
+pub fn build_codec_with_limits(root_pos: Option<TypePos>,
+    descriptors: &[Descriptor], limits: &Limits)
+    -> Result<Arc<dyn Codec>, CodecError>
+{
+    let dec = CodecBuilder::new(false, descriptors, limits);
     match root_pos {
         Some(pos) => dec.build(pos),
         None => Ok(Arc::new(Nothing {})),
@@ -266,7 +456,14 @@ pub fn build_input_codec(root_pos: Option<TypePos>,
     descriptors: &[Descriptor])
     -> Result<Arc<dyn Codec>, CodecError>
 {
-    let dec = CodecBuilder { input: true, descriptors };
+    build_input_codec_with_limits(root_pos, descriptors, &Limits::default())
+}
+
+pub fn build_input_codec_with_limits(root_pos: Option<TypePos>,
+    descriptors: &[Descriptor], limits: &Limits)
+    -> Result<Arc<dyn Codec>, CodecError>
+{
+    let dec = CodecBuilder::new(true, descriptors, limits);
     match root_pos {
         Some(pos) => dec.build(pos),
         None => Ok(Arc::new(Nothing {})),
@@ -275,6 +472,12 @@ pub fn build_input_codec(root_pos: Option<TypePos>,
 
 
 pub fn scalar_codec(uuid: &UuidVal) -> Result<Arc<dyn Codec>, CodecError> {
+    scalar_codec_with_limits(uuid, &Limits::default())
+}
+
+pub fn scalar_codec_with_limits(uuid: &UuidVal, limits: &Limits)
+    -> Result<Arc<dyn Codec>, CodecError>
+{
     match *uuid {
         STD_UUID => Ok(Arc::new(Uuid {})),
         STD_STR => Ok(Arc::new(Str {})),
@@ -284,7 +487,9 @@ pub fn scalar_codec(uuid: &UuidVal) -> Result<Arc<dyn Codec>, CodecError> {
         STD_INT64 => Ok(Arc::new(Int64 {})),
         STD_FLOAT32 => Ok(Arc::new(Float32 {})),
         STD_FLOAT64 => Ok(Arc::new(Float64 {})),
-        STD_DECIMAL => Ok(Arc::new(Decimal {})),
+        STD_DECIMAL => Ok(Arc::new(Decimal {
+            max_digits: limits.max_decimal_digits,
+        })),
         STD_BOOL => Ok(Arc::new(Bool {})),
         STD_DATETIME => Ok(Arc::new(Datetime {})),
         CAL_LOCAL_DATETIME => Ok(Arc::new(LocalDatetime {})),
@@ -292,11 +497,24 @@ pub fn scalar_codec(uuid: &UuidVal) -> Result<Arc<dyn Codec>, CodecError> {
         CAL_LOCAL_TIME => Ok(Arc::new(LocalTime {})),
         STD_DURATION => Ok(Arc::new(Duration {})),
         STD_JSON => Ok(Arc::new(Json {})),
-        STD_BIGINT => Ok(Arc::new(BigInt {})),
+        STD_BIGINT => Ok(Arc::new(BigInt {
+            max_digits: limits.max_bigint_digits,
+        })),
         _ => return errors::UndefinedBaseScalar { uuid: uuid.clone() }.fail()?,
     }
 }
 
+macro_rules! sealed {
+    ($($name: ident),*) => {
+        $(impl Sealed for $name {})*
+    }
+}
+
+sealed!(Int32, Int16, Int64, Float32, Float64, Str, Bytes, Duration, Uuid,
+    Nothing, Object, Set, Decimal, BigInt, Bool, Datetime, LocalDatetime,
+    LocalDate, LocalTime, Json, Scalar, Tuple, InputTuple, NamedTuple,
+    InputNamedTuple, Array, Enum);
+
 impl Codec for Int32 {
     fn decode(&self, buf: &mut Cursor<Buf>) -> Result<Value, DecodeError> {
         ensure!(buf.remaining() >= 4, errors::Underflow);
@@ -613,6 +831,7 @@ impl<'a> From<&'a [descriptors::ShapeElement]> for ObjectShape {
                         flag_implicit,
                         flag_link_property,
                         flag_link,
+                        cardinality,
                         name,
                         type_pos: _,
                     } = e;
@@ -620,6 +839,7 @@ impl<'a> From<&'a [descriptors::ShapeElement]> for ObjectShape {
                         flag_implicit: *flag_implicit,
                         flag_link_property: *flag_link_property,
                         flag_link: *flag_link,
+                        cardinality: *cardinality,
                         name: name.clone(),
                     }
                 }).collect(),
@@ -662,10 +882,51 @@ impl Set {
     {
         Ok(Set {
             element: dec.build(d.type_pos)?,
+            order: dec.limits.set_order,
         })
     }
 }
 
+/// Encodes elements of an array or a set
+///
+/// Number of elements is written after all of them are encoded, so items
+/// can be produced by an iterator of unknown length.
+fn encode_items<I>(element: &dyn Codec, buf: &mut BytesMut, items: I)
+    -> Result<(), EncodeError>
+    where I: Iterator,
+          I::Item: Borrow<Value>,
+{
+    let start = buf.len();
+    buf.reserve(20);
+    buf.put_u32(1);  // ndims
+    buf.put_u32(0);  // reserved0
+    buf.put_u32(0);  // reserved1
+    buf.put_u32(0);  // size, replaced after serializing all values
+    buf.put_u32(1);  // lower
+    let mut size = 0usize;
+    for item in items {
+        buf.reserve(4);
+        let pos = buf.len();
+        buf.put_u32(0);  // replaced after serializing a value
+        element.encode(buf, item.borrow())?;
+        let len = buf.len()-pos-4;
+        buf[pos..pos+4].copy_from_slice(&u32::try_from(len)
+                .ok().context(errors::ElementTooLong)?
+                .to_be_bytes());
+        size += 1;
+    }
+    if size == 0 {
+        // empty arrays have no dimensions
+        buf.truncate(start + 12);
+        buf[start..start+4].copy_from_slice(&0u32.to_be_bytes());
+        return Ok(());
+    }
+    buf[start+12..start+16].copy_from_slice(&u32::try_from(size)
+            .ok().context(errors::ArrayTooLong)?
+            .to_be_bytes());
+    Ok(())
+}
+
 impl Codec for Set {
     fn decode(&self, buf: &mut Cursor<Buf>) -> Result<Value, DecodeError> {
         ensure!(buf.remaining() >= 12, errors::Underflow);
@@ -681,15 +942,21 @@ impl Codec for Set {
         let size = buf.get_u32() as usize;
         let lower = buf.get_u32();
         ensure!(lower == 1, errors::InvalidSetShape);
-        let mut items = Vec::with_capacity(size);
+        let mut chunks = Vec::with_capacity(size);
         for _ in 0..size {
             ensure!(buf.remaining() >= 4, errors::Underflow);
             let len = buf.get_u32() as usize;
             ensure!(buf.remaining() >= len, errors::Underflow);
             let off = buf.position() as usize;
-            let mut chunk = Cursor::new(buf.get_ref().slice(off..off + len));
+            chunks.push(buf.get_ref().slice(off..off + len));
             buf.advance(len);
-            items.push(self.element.decode_value(&mut chunk)?);
+        }
+        if self.order == SetOrder::Sorted {
+            chunks.sort();
+        }
+        let mut items = Vec::with_capacity(size);
+        for chunk in chunks {
+            items.push(self.element.decode_value(&mut Cursor::new(chunk))?);
         }
         Ok(Value::Set(items))
     }
@@ -700,31 +967,13 @@ impl Codec for Set {
             Value::Set(items) => items,
             _ => Err(errors::invalid_value(type_name::<Self>(), val))?,
         };
-        if items.is_empty() {
-            buf.reserve(12);
-            buf.put_u32(0);  // ndims
-            buf.put_u32(0);  // reserved0
-            buf.put_u32(0);  // reserved1
-            return Ok(());
-        }
-        buf.reserve(20);
-        buf.put_u32(1);  // ndims
-        buf.put_u32(0);  // reserved0
-        buf.put_u32(0);  // reserved1
-        buf.put_u32(items.len().try_into().ok()
-            .context(errors::ArrayTooLong)?);
-        buf.put_u32(1);  // lower
-        for item in items {
-            buf.reserve(4);
-            let pos = buf.len();
-            buf.put_u32(0);  // replaced after serializing a value
-            self.element.encode(buf, item)?;
-            let len = buf.len()-pos-4;
-            buf[pos..pos+4].copy_from_slice(&u32::try_from(len)
-                    .ok().context(errors::ElementTooLong)?
-                    .to_be_bytes());
-        }
-        Ok(())
+        encode_items(&*self.element, buf, items.iter())
+    }
+    fn encode_iter(&self, buf: &mut BytesMut,
+                   items: &mut dyn Iterator<Item=Value>)
+        -> Result<(), EncodeError>
+    {
+        encode_items(&*self.element, buf, items)
     }
 }
 
@@ -739,6 +988,10 @@ impl Codec for Decimal {
             _ => errors::BadSign.fail()?,
         };
         let decimal_digits = buf.get_u16();
+        ensure!(ndigits <= self.max_digits, errors::TooManyDigits {
+            digits: ndigits,
+            max_digits: self.max_digits,
+        });
         ensure!(buf.remaining() >= ndigits*2, errors::Underflow);
         let mut digits = Vec::with_capacity(ndigits);
         for _ in 0..ndigits {
@@ -757,7 +1010,7 @@ impl Codec for Decimal {
         };
         buf.reserve(8 + val.digits.len()*2);
         buf.put_u16(val.digits.len().try_into().ok()
-                .context(errors::BigIntTooLong)?);
+                .context(errors::DecimalTooLong)?);
         buf.put_i16(val.weight);
         buf.put_u16(if val.negative { 0x4000 } else { 0x0000 });
         buf.put_u16(val.decimal_digits);
@@ -780,8 +1033,12 @@ impl Codec for BigInt {
         };
         let decimal_digits = buf.get_u16();
         ensure!(decimal_digits == 0, errors::NonZeroReservedBytes);
-        let mut digits = Vec::with_capacity(ndigits);
+        ensure!(ndigits <= self.max_digits, errors::TooManyDigits {
+            digits: ndigits,
+            max_digits: self.max_digits,
+        });
         ensure!(buf.remaining() >= ndigits*2, errors::Underflow);
+        let mut digits = Vec::with_capacity(ndigits);
         for _ in 0..ndigits {
             digits.push(buf.get_u16());
         }
@@ -831,18 +1088,11 @@ impl Codec for Bool {
 
 impl Codec for Datetime {
     fn decode(&self, buf: &mut Cursor<Buf>) -> Result<Value, DecodeError> {
-        use std::time::{Duration};
-
         ensure!(buf.remaining() >= 8, errors::Underflow);
         let micros = buf.get_i64();
-        let postgres_epoch: SystemTime = UNIX_EPOCH +
-            std::time::Duration::from_secs(946684800);
-        let val = if micros > 0 {
-            postgres_epoch + Duration::from_micros(micros as u64)
-        } else {
-            postgres_epoch - Duration::from_micros((-micros) as u64)
-        };
-        Ok(Value::Datetime(val))
+        let datetime = value::Datetime::try_from_micros(micros).ok()
+            .context(errors::InvalidDate)?;
+        Ok(Value::Datetime(datetime))
     }
     fn encode(&self, buf: &mut BytesMut, val: &Value)
         -> Result<(), EncodeError>
@@ -852,22 +1102,7 @@ impl Codec for Datetime {
             _ => Err(errors::invalid_value(type_name::<Self>(), val))?,
         };
         buf.reserve(8);
-        let postgres_epoch: SystemTime = UNIX_EPOCH +
-            std::time::Duration::from_secs(946684800);
-        if *val >= postgres_epoch {
-            buf.put_i64(val.duration_since(postgres_epoch)
-                .ok().context(errors::DatetimeRange)?
-                .as_micros()
-                .try_into()
-                .ok().context(errors::DatetimeRange)?);
-        } else {
-            let micros: i64 = postgres_epoch.duration_since(*val)
-                .ok().context(errors::DatetimeRange)?
-                .as_micros()
-                .try_into()
-                .ok().context(errors::DatetimeRange)?;
-            buf.put_i64(-micros);
-        }
+        buf.put_i64(val.micros);
         Ok(())
     }
 }
@@ -876,8 +1111,9 @@ impl Codec for LocalDatetime {
     fn decode(&self, buf: &mut Cursor<Buf>) -> Result<Value, DecodeError> {
         ensure!(buf.remaining() >= 8, errors::Underflow);
         let micros = buf.get_i64();
-        Ok(Value::LocalDatetime(
-            value::LocalDatetime { micros }))
+        let datetime = value::LocalDatetime::try_from_micros(micros).ok()
+            .context(errors::InvalidDate)?;
+        Ok(Value::LocalDatetime(datetime))
     }
     fn encode(&self, buf: &mut BytesMut, val: &Value)
         -> Result<(), EncodeError>
@@ -940,7 +1176,7 @@ impl Codec for Json {
             .context(errors::InvalidUtf8)?
             .to_owned();
         buf.advance(val.len());
-        Ok(Value::Json(val))
+        Ok(Value::Json(value::Json::new_unchecked(val)))
     }
     fn encode(&self, buf: &mut BytesMut, val: &Value)
         -> Result<(), EncodeError>
@@ -1186,31 +1422,13 @@ impl Codec for Array {
             Value::Array(items) => items,
             _ => Err(errors::invalid_value(type_name::<Self>(), val))?,
         };
-        if items.is_empty() {
-            buf.reserve(12);
-            buf.put_u32(0);  // ndims
-            buf.put_u32(0);  // reserved0
-            buf.put_u32(0);  // reserved1
-            return Ok(());
-        }
-        buf.reserve(20);
-        buf.put_u32(1);  // ndims
-        buf.put_u32(0);  // reserved0
-        buf.put_u32(0);  // reserved1
-        buf.put_u32(items.len().try_into().ok()
-            .context(errors::ArrayTooLong)?);
-        buf.put_u32(1);  // lower
-        for item in items {
-            buf.reserve(4);
-            let pos = buf.len();
-            buf.put_u32(0);  // replaced after serializing a value
-            self.element.encode(buf, item)?;
-            let len = buf.len()-pos-4;
-            buf[pos..pos+4].copy_from_slice(&u32::try_from(len)
-                    .ok().context(errors::ElementTooLong)?
-                    .to_be_bytes());
-        }
-        Ok(())
+        encode_items(&*self.element, buf, items.iter())
+    }
+    fn encode_iter(&self, buf: &mut BytesMut,
+                   items: &mut dyn Iterator<Item=Value>)
+        -> Result<(), EncodeError>
+    {
+        encode_items(&*self.element, buf, items)
     }
 }
 
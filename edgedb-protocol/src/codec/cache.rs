@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use uuid::Uuid;
+
+use crate::client_message::{IoFormat, Cardinality};
+use crate::codec::{Codec, Limits};
+use crate::descriptors::{OutputTypedesc, InputTypedesc, TypePos};
+use crate::errors::CodecError;
+
+
+/// A small LRU cache of codecs
+///
+/// Codecs are keyed by type descriptor id, which the server derives from
+/// the contents of the descriptor, so the same codec can be reused for
+/// every statement (and every connection) that returns the same type.
+#[derive(Debug)]
+pub struct CodecCache {
+    capacity: usize,
+    limits: Limits,
+    inner: Mutex<Lru<Key, Arc<dyn Codec>>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Key {
+    id: Uuid,
+    root_pos: Option<TypePos>,
+    input: bool,
+}
+
+/// A small LRU cache of prepared statements
+///
+/// Meant to be kept per connection: statements are keyed by query text,
+/// output format and expected cardinality, so a repeated query can skip
+/// the Prepare and Describe round trips. Codecs are stored along with the
+/// statement, so they are not rebuilt either.
+#[derive(Debug)]
+pub struct StatementCache {
+    capacity: usize,
+    inner: Mutex<Lru<StatementKey, Arc<PreparedStatement>>>,
+}
+
+/// Result of preparing a statement, as stored in `StatementCache`
+#[derive(Debug, Clone)]
+pub struct PreparedStatement {
+    /// Name the statement was prepared with, used by `Execute` before
+    /// protocol 1.0 (`Execute1` sends the query text instead)
+    pub statement_name: Bytes,
+    pub cardinality: Cardinality,
+    pub input_typedesc_id: Uuid,
+    pub output_typedesc_id: Uuid,
+    pub input_codec: Arc<dyn Codec>,
+    pub output_codec: Arc<dyn Codec>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct StatementKey {
+    query: String,
+    io_format: IoFormat,
+    expected_cardinality: Cardinality,
+}
+
+#[derive(Debug)]
+struct Lru<K, V> {
+    tick: u64,
+    items: HashMap<K, (u64, V)>,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Lru<K, V> {
+    fn new(capacity: usize) -> Lru<K, V> {
+        Lru {
+            tick: 0,
+            items: HashMap::with_capacity(capacity),
+        }
+    }
+    fn get(&mut self, key: &K) -> Option<V> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.items.get_mut(key).map(|item| {
+            item.0 = tick;
+            item.1.clone()
+        })
+    }
+    fn insert(&mut self, key: K, value: V, capacity: usize) {
+        if self.items.len() >= capacity && !self.items.contains_key(&key) {
+            let oldest = self.items.iter()
+                .min_by_key(|(_, (tick, _))| *tick)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.items.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.items.insert(key, (self.tick, value));
+    }
+}
+
+impl CodecCache {
+    pub fn new(capacity: usize) -> CodecCache {
+        CodecCache::with_limits(capacity, Limits::default())
+    }
+    pub fn with_limits(capacity: usize, limits: Limits) -> CodecCache {
+        CodecCache {
+            capacity,
+            limits,
+            inner: Mutex::new(Lru::new(capacity)),
+        }
+    }
+    pub fn output(&self, desc: &OutputTypedesc)
+        -> Result<Arc<dyn Codec>, CodecError>
+    {
+        let key = Key {
+            id: desc.id().clone(),
+            root_pos: desc.root_pos(),
+            input: false,
+        };
+        self.get_or_build(key, || desc.build_codec_with_limits(&self.limits))
+    }
+    pub fn input(&self, desc: &InputTypedesc)
+        -> Result<Arc<dyn Codec>, CodecError>
+    {
+        let key = Key {
+            id: desc.id().clone(),
+            root_pos: Some(desc.root_pos()),
+            input: true,
+        };
+        self.get_or_build(key, || desc.build_codec_with_limits(&self.limits))
+    }
+    pub fn len(&self) -> usize {
+        self.inner.lock().expect("cache is not poisoned").items.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn clear(&self) {
+        self.inner.lock().expect("cache is not poisoned").items.clear();
+    }
+    fn get_or_build<F>(&self, key: Key, build: F)
+        -> Result<Arc<dyn Codec>, CodecError>
+        where F: FnOnce() -> Result<Arc<dyn Codec>, CodecError>
+    {
+        let cached = self.inner.lock().expect("cache is not poisoned")
+            .get(&key);
+        if let Some(codec) = cached {
+            return Ok(codec);
+        }
+        // codec is built without holding the lock, so concurrent callers
+        // may build the same codec twice, which is harmless
+        let codec = build()?;
+        if self.capacity == 0 {
+            return Ok(codec);
+        }
+        self.inner.lock().expect("cache is not poisoned")
+            .insert(key, codec.clone(), self.capacity);
+        Ok(codec)
+    }
+}
+
+impl StatementCache {
+    pub fn new(capacity: usize) -> StatementCache {
+        StatementCache {
+            capacity,
+            inner: Mutex::new(Lru::new(capacity)),
+        }
+    }
+    pub fn get(&self, query: &str, io_format: IoFormat,
+               expected_cardinality: Cardinality)
+        -> Option<Arc<PreparedStatement>>
+    {
+        let key = StatementKey {
+            query: query.into(),
+            io_format,
+            expected_cardinality,
+        };
+        self.inner.lock().expect("cache is not poisoned").get(&key)
+    }
+    pub fn insert(&self, query: &str, io_format: IoFormat,
+                  expected_cardinality: Cardinality,
+                  statement: PreparedStatement)
+        -> Arc<PreparedStatement>
+    {
+        let statement = Arc::new(statement);
+        if self.capacity == 0 {
+            return statement;
+        }
+        let key = StatementKey {
+            query: query.into(),
+            io_format,
+            expected_cardinality,
+        };
+        self.inner.lock().expect("cache is not poisoned")
+            .insert(key, statement.clone(), self.capacity);
+        statement
+    }
+    pub fn len(&self) -> usize {
+        self.inner.lock().expect("cache is not poisoned").items.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Removes all statements, e.g. after a schema change
+    pub fn clear(&self) {
+        self.inner.lock().expect("cache is not poisoned").items.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use uuid::Uuid;
+
+    use crate::codec;
+    use crate::descriptors::{OutputTypedesc, Descriptor, TypePos};
+    use crate::descriptors::BaseScalarTypeDescriptor;
+    use crate::client_message::{IoFormat, Cardinality};
+    use super::{CodecCache, StatementCache, PreparedStatement};
+
+    fn scalar(id: u128) -> OutputTypedesc {
+        OutputTypedesc {
+            array: vec![
+                Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+                    id: Uuid::from_u128(id),
+                }),
+            ],
+            root_id: Uuid::from_u128(id),
+            root_pos: Some(TypePos(0)),
+        }
+    }
+
+    #[test]
+    fn reuse() {
+        let cache = CodecCache::new(2);
+        let a = cache.output(&scalar(0x101)).unwrap();
+        let b = cache.output(&scalar(0x101)).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let cache = CodecCache::new(2);
+        let str_codec = cache.output(&scalar(0x101)).unwrap();
+        let int_codec = cache.output(&scalar(0x105)).unwrap();
+        // touch str, so int is the oldest one
+        cache.output(&scalar(0x101)).unwrap();
+        cache.output(&scalar(0x109)).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert!(Arc::ptr_eq(&str_codec,
+                            &cache.output(&scalar(0x101)).unwrap()));
+        assert!(!Arc::ptr_eq(&int_codec,
+                             &cache.output(&scalar(0x105)).unwrap()));
+    }
+
+    #[test]
+    fn errors_are_not_cached() {
+        let cache = CodecCache::new(2);
+        assert!(cache.output(&scalar(0xFFFF)).is_err());
+        assert!(cache.is_empty());
+        assert!(cache.output(&scalar(codec::STD_STR.as_u128())).is_ok());
+    }
+
+    fn statement(id: u128) -> PreparedStatement {
+        let codec = scalar(id).build_codec().unwrap();
+        PreparedStatement {
+            statement_name: Bytes::from(format!("s{:x}", id)),
+            cardinality: Cardinality::One,
+            input_typedesc_id: Uuid::from_u128(0xFF),
+            output_typedesc_id: Uuid::from_u128(id),
+            input_codec: codec.clone(),
+            output_codec: codec,
+        }
+    }
+
+    #[test]
+    fn statements() {
+        use IoFormat::{Binary, Json};
+        use Cardinality::{One, Many};
+
+        let cache = StatementCache::new(2);
+        cache.insert("SELECT 1", Binary, One, statement(0x105));
+        cache.insert("SELECT 'x'", Binary, One, statement(0x101));
+        assert!(cache.get("SELECT 1", Json, One).is_none());
+        assert!(cache.get("SELECT 1", Binary, Many).is_none());
+        let stmt = cache.get("SELECT 1", Binary, One).unwrap();
+        assert_eq!(stmt.output_typedesc_id, Uuid::from_u128(0x105));
+        assert_eq!(&stmt.statement_name[..], b"s105");
+
+        // 'x' is the least recently used
+        cache.insert("SELECT true", Binary, One, statement(0x109));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("SELECT 'x'", Binary, One).is_none());
+        assert!(cache.get("SELECT 1", Binary, One).is_some());
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
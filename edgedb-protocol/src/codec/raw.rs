@@ -5,13 +5,19 @@ use bytes::{Bytes, Buf};
 use uuid::Uuid;
 
 use crate::errors::{self, DecodeError};
+use crate::sealed::Sealed;
 use snafu::{ResultExt, ensure};
 
 
-pub trait RawCodec: Sized {
+pub trait RawCodec: Sealed + Sized {
     fn decode_raw(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError>;
 }
 
+impl Sealed for String {}
+impl Sealed for Uuid {}
+impl Sealed for bool {}
+impl Sealed for i64 {}
+
 impl RawCodec for String {
     fn decode_raw(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
         let val = str::from_utf8(&buf.bytes())
@@ -25,10 +31,9 @@ impl RawCodec for String {
 impl RawCodec for Uuid {
     fn decode_raw(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
         ensure!(buf.remaining() >= 16, errors::Underflow);
-        let uuid = Uuid::from_slice(buf.bytes())
-            .context(errors::InvalidUuid)?;
-        buf.advance(16);
-        Ok(uuid)
+        let mut bytes = [0u8; 16];
+        buf.copy_to_slice(&mut bytes);
+        Ok(Uuid::from_bytes(bytes))
     }
 }
 
@@ -0,0 +1,116 @@
+//! Thread-local scratch space for encoding small values
+//!
+//! Encoded arguments are split off the per-thread buffer, so once the
+//! previous `Bytes` are dropped (usually right after the message is
+//! written to the socket) the same allocation is used again.
+use std::cell::{Cell, RefCell};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::codec::Codec;
+use crate::errors::EncodeError;
+use crate::value::Value;
+
+/// Capacity reserved when the scratch buffer runs low
+pub const SCRATCH_SIZE: usize = 4096;
+/// Free space below which the scratch buffer is refilled
+pub const MIN_FREE: usize = 512;
+/// Encodings larger than this detach the scratch buffer, so a single
+/// large query doesn't keep a large allocation alive for the thread
+pub const MAX_RETAINED: usize = 65536;
+
+/// Per-thread counters of scratch buffer usage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScratchStats {
+    /// Number of values encoded
+    pub encoded: u64,
+    /// Number of encodings that fit into already reserved space
+    pub reused: u64,
+    /// Total number of bytes produced
+    pub bytes: u64,
+}
+
+thread_local! {
+    static SCRATCH: RefCell<BytesMut> = RefCell::new(BytesMut::new());
+    static STATS: Cell<ScratchStats> = Cell::new(ScratchStats::default());
+}
+
+/// Encodes value using thread-local scratch buffer
+pub fn encode(codec: &dyn Codec, value: &Value)
+    -> Result<Bytes, EncodeError>
+{
+    SCRATCH.with(|scratch| {
+        let mut buf = scratch.borrow_mut();
+        buf.clear();
+        let reused = buf.capacity() >= MIN_FREE;
+        if !reused {
+            buf.reserve(SCRATCH_SIZE);
+        }
+        let capacity = buf.capacity();
+        let result = codec.encode(&mut buf, value);
+        if let Err(e) = result {
+            buf.clear();
+            return Err(e);
+        }
+        let data = buf.split().freeze();
+        if data.len() > MAX_RETAINED {
+            *buf = BytesMut::new();
+        }
+        STATS.with(|stats| {
+            let mut s = stats.get();
+            s.encoded += 1;
+            if reused && data.len() <= capacity {
+                s.reused += 1;
+            }
+            s.bytes += data.len() as u64;
+            stats.set(s);
+        });
+        Ok(data)
+    })
+}
+
+/// Returns scratch buffer statistics for the current thread
+pub fn stats() -> ScratchStats {
+    STATS.with(|stats| stats.get())
+}
+
+/// Resets scratch buffer statistics for the current thread
+pub fn reset_stats() {
+    STATS.with(|stats| stats.set(ScratchStats::default()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::codec::{self, Codec};
+    use crate::value::Value;
+    use super::{encode, stats, reset_stats};
+
+    #[test]
+    fn reuse() {
+        reset_stats();
+        let codec: Arc<dyn Codec> = Arc::new(codec::Int64);
+        let mut buf = bytes::BytesMut::new();
+        codec.encode(&mut buf, &Value::Int64(7)).unwrap();
+
+        let first = encode(&*codec, &Value::Int64(7)).unwrap();
+        assert_eq!(first, buf.freeze());
+        drop(first);
+        for _ in 0..10 {
+            encode(&*codec, &Value::Int64(7)).unwrap();
+        }
+        let stats = stats();
+        assert_eq!(stats.encoded, 11);
+        assert_eq!(stats.reused, 10);
+        assert_eq!(stats.bytes, 88);
+    }
+
+    #[test]
+    fn error() {
+        reset_stats();
+        let codec: Arc<dyn Codec> = Arc::new(codec::Int64);
+        assert!(encode(&*codec, &Value::Str("x".into())).is_err());
+        assert_eq!(stats().encoded, 0);
+    }
+}
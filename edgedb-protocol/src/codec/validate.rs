@@ -0,0 +1,231 @@
+//! Client-side validation of query arguments against input descriptor
+//!
+//! Codecs stop at the first value that can't be encoded and report only
+//! the codec name. Validation walks the whole descriptor tree along with
+//! the value, so the error names the argument (and the element inside it)
+//! and the type the server expects.
+use snafu::ensure;
+
+use crate::codec::Limits;
+use crate::descriptors::{Descriptor, TypePos};
+use crate::errors::{self, EncodeError};
+use crate::type_names::{scalar_type, type_name};
+use crate::value::Value;
+
+
+fn child(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        format!("${}", name)
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn mismatch(descriptors: &[Descriptor], pos: TypePos, path: &str,
+            value: &Value)
+    -> EncodeError
+{
+    let path = if path.is_empty() { "arguments" } else { path };
+    errors::ArgumentMismatch {
+        path,
+        expected: type_name(descriptors, pos),
+        value_type: value.kind(),
+    }.fail::<()>().unwrap_err()
+}
+
+/// Checks that `value` can be encoded with type at `pos`
+///
+/// Positions that are out of range and unknown scalars are skipped, those
+/// are reported when building the codec. Descriptors nested deeper than
+/// `limits.max_depth` are rejected, like in `build_codec_with_limits`.
+pub(crate) fn validate(descriptors: &[Descriptor], limits: &Limits,
+                       pos: TypePos, path: &str, value: &Value)
+    -> Result<(), EncodeError>
+{
+    Validator { descriptors, max_depth: limits.max_depth }
+        .validate(pos, path, value, 0)
+}
+
+struct Validator<'a> {
+    descriptors: &'a [Descriptor],
+    max_depth: usize,
+}
+
+impl Validator<'_> {
+    fn validate(&self, pos: TypePos, path: &str, value: &Value,
+                depth: usize)
+        -> Result<(), EncodeError>
+    {
+        use Descriptor as D;
+        use Value as V;
+        ensure!(depth < self.max_depth,
+                errors::ArgumentsTooDeep { max_depth: self.max_depth });
+        let descriptors = self.descriptors;
+        let fail = || mismatch(descriptors, pos, path, value);
+        match (descriptors.get(pos.0 as usize), value) {
+            (None, _) => {}
+            (Some(D::BaseScalar(d)), _) => {
+                if let Some(scalar) = scalar_type(&d.id) {
+                    if value.kind() != scalar.value_kind {
+                        return Err(fail());
+                    }
+                }
+            }
+            (Some(D::Scalar(d)), _) => {
+                self.validate(d.base_type_pos, path, value, depth+1)?;
+            }
+            (Some(D::Tuple(d)), V::Tuple(items)) => {
+                if d.element_types.len() != items.len() {
+                    return Err(fail());
+                }
+                for (idx, (&pos, item)) in
+                    d.element_types.iter().zip(items).enumerate()
+                {
+                    self.validate(pos, &child(path, &idx.to_string()),
+                                  item, depth+1)?;
+                }
+            }
+            (Some(D::NamedTuple(d)), V::NamedTuple { shape, fields }) => {
+                let same_names = d.elements.len() == shape.elements.len() &&
+                    d.elements.iter().zip(&shape.elements)
+                        .all(|(a, b)| a.name == b.name);
+                if !same_names {
+                    return Err(fail());
+                }
+                for (el, item) in d.elements.iter().zip(fields) {
+                    self.validate(el.type_pos, &child(path, &el.name),
+                                  item, depth+1)?;
+                }
+            }
+            (Some(D::Array(d)), V::Array(items)) => {
+                self.validate_items(d.type_pos, path, items, depth+1)?;
+            }
+            (Some(D::Set(d)), V::Set(items)) => {
+                self.validate_items(d.type_pos, path, items, depth+1)?;
+            }
+            (Some(D::Enumeration(d)), V::Enum(val)) => {
+                if !d.members.iter().any(|m| m[..] == val[..]) {
+                    return Err(fail());
+                }
+            }
+            (Some(D::TypeAnnotation(_)), _) => {}
+            _ => return Err(fail()),
+        }
+        Ok(())
+    }
+
+    fn validate_items(&self, pos: TypePos, path: &str, items: &[Value],
+                      depth: usize)
+        -> Result<(), EncodeError>
+    {
+        for (idx, item) in items.iter().enumerate() {
+            self.validate(pos, &format!("{}[{}]", path, idx), item, depth)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::codec::{STD_STR, STD_INT64, Limits};
+    use crate::descriptors::{Descriptor, InputTypedesc, TypePos};
+    use crate::descriptors::{BaseScalarTypeDescriptor, ArrayTypeDescriptor};
+    use crate::descriptors::ScalarTypeDescriptor;
+    use crate::descriptors::{NamedTupleTypeDescriptor, TupleElement};
+    use crate::value::{Value, NamedTupleBuilder};
+
+    fn args() -> InputTypedesc {
+        InputTypedesc {
+            array: vec![
+                Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+                    id: STD_STR,
+                }),
+                Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+                    id: STD_INT64,
+                }),
+                Descriptor::Array(ArrayTypeDescriptor {
+                    id: Uuid::from_u128(0x1000),
+                    type_pos: TypePos(1),
+                    dimensions: vec![None],
+                }),
+                Descriptor::NamedTuple(NamedTupleTypeDescriptor {
+                    id: Uuid::from_u128(0x1001),
+                    elements: vec![
+                        TupleElement {
+                            name: "name".into(),
+                            type_pos: TypePos(0),
+                        },
+                        TupleElement {
+                            name: "ids".into(),
+                            type_pos: TypePos(2),
+                        },
+                    ],
+                }),
+            ],
+            root_id: Uuid::from_u128(0x1001),
+            root_pos: TypePos(3),
+        }
+    }
+
+    #[test]
+    fn valid() {
+        let value = NamedTupleBuilder::new()
+            .field("name", Value::Str("x".into()))
+            .field("ids", Value::Array(vec![Value::Int64(1)]))
+            .build();
+        args().validate_args(&value).unwrap();
+    }
+
+    #[test]
+    fn path() {
+        let value = NamedTupleBuilder::new()
+            .field("name", Value::Str("x".into()))
+            .field("ids", Value::Array(vec![
+                Value::Int64(1),
+                Value::Int32(2),
+            ]))
+            .build();
+        assert_eq!(args().validate_args(&value).unwrap_err().to_string(),
+            "invalid type of $ids[1]: expected std::int64, got int32");
+    }
+
+    #[test]
+    fn wrong_names() {
+        let value = NamedTupleBuilder::new()
+            .field("name", Value::Str("x".into()))
+            .build();
+        assert_eq!(args().validate_args(&value).unwrap_err().to_string(),
+            "invalid type of arguments: \
+             expected tuple<name: std::str, ids: array<std::int64>>, \
+             got named_tuple");
+    }
+
+    #[test]
+    fn max_depth() {
+        // scalar referring to itself
+        let desc = InputTypedesc {
+            array: vec![
+                Descriptor::Scalar(ScalarTypeDescriptor {
+                    id: Uuid::from_u128(0x1000),
+                    base_type_pos: TypePos(0),
+                }),
+            ],
+            root_id: Uuid::from_u128(0x1000),
+            root_pos: TypePos(0),
+        };
+        assert_eq!(desc.validate_args(&Value::Int64(1))
+                   .unwrap_err().to_string(),
+                   "arguments are nested deeper than 64");
+
+        let limits = Limits { max_depth: 2, ..Limits::default() };
+        let value = NamedTupleBuilder::new()
+            .field("name", Value::Str("x".into()))
+            .field("ids", Value::Array(vec![Value::Int64(1)]))
+            .build();
+        assert!(args().validate_args_with_limits(&value, &limits).is_err());
+        let limits = Limits { max_depth: 3, ..Limits::default() };
+        args().validate_args_with_limits(&value, &limits).unwrap();
+    }
+}
@@ -1,7 +1,53 @@
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+bitflags::bitflags! {
+    /// What a query is allowed to do (client) or does (server)
+    pub struct Capabilities: u64 {
+        const MODIFICATIONS = 0b00000001;
+        const SESSION_CONFIG = 0b00000010;
+        const TRANSACTION = 0b00000100;
+        const DDL = 0b00001000;
+        const PERSISTENT_CONFIG = 0b00010000;
+        // includes bits not known yet, so that they are preserved when
+        // decoding messages
+        const ALL = 0xffff_ffff_ffff_ffff;
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum Cardinality {
     NoResult = 0x6e,
     One = 0x6f,
     Many = 0x6d,
 }
 
+
+/// Cardinality of an object shape element
+///
+/// Sent by the server since protocol 0.13.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature="serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ShapeCardinality {
+    NoResult = 0x6e,
+    AtMostOne = 0x6f,
+    One = 0x41,
+    Many = 0x6d,
+    AtLeastOne = 0x4d,
+}
+
+impl ShapeCardinality {
+    /// Returns true if the element may be an empty set
+    pub fn is_optional(&self) -> bool {
+        use ShapeCardinality::*;
+        match self {
+            NoResult | AtMostOne | Many => true,
+            One | AtLeastOne => false,
+        }
+    }
+    /// Returns true if the element may contain more than one value
+    pub fn is_multi(&self) -> bool {
+        use ShapeCardinality::*;
+        match self {
+            Many | AtLeastOne => true,
+            NoResult | AtMostOne | One => false,
+        }
+    }
+}
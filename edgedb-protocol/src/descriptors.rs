@@ -6,16 +6,25 @@ use uuid::Uuid;
 use snafu::{ensure, OptionExt};
 
 use crate::encoding::{Decode};
-use crate::errors::{self, DecodeError, CodecError};
+use crate::errors::{self, DecodeError, CodecError, EncodeError};
+use crate::features::ProtocolVersion;
 use crate::errors::{InvalidTypeDescriptor, UnexpectedTypePos};
-use crate::codec::{Codec, build_codec, build_input_codec};
+use crate::codec::{Codec, Limits, build_codec, build_input_codec};
+use crate::codec::{build_codec_with_limits, build_input_codec_with_limits};
+use crate::codec::validate::validate;
 use crate::queryable;
+use crate::value::Value;
+pub use crate::common::ShapeCardinality;
 
+// Serde support for descriptors is behind `with-serde` rather than `serde`
+// because serializing `Uuid` needs `uuid/serde` enabled
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TypePos(pub u16);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Descriptor {
     Set(SetDescriptor),
     ObjectShape(ObjectShapeDescriptor),
@@ -28,71 +37,86 @@ pub enum Descriptor {
     TypeAnnotation(TypeAnnotationDescriptor),
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OutputTypedesc {
+    #[cfg_attr(feature="with-serde", serde(rename="descriptors"))]
     pub(crate) array: Vec<Descriptor>,
-    #[allow(dead_code)] // TODO
     pub(crate) root_id: Uuid,
     pub(crate) root_pos: Option<TypePos>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InputTypedesc {
+    #[cfg_attr(feature="with-serde", serde(rename="descriptors"))]
     pub(crate) array: Vec<Descriptor>,
-    #[allow(dead_code)] // TODO
     pub(crate) root_id: Uuid,
     pub(crate) root_pos: TypePos,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetDescriptor {
     pub id: Uuid,
     pub type_pos: TypePos,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObjectShapeDescriptor {
     pub id: Uuid,
     pub elements: Vec<ShapeElement>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShapeElement {
     pub flag_implicit: bool,
     pub flag_link_property: bool,
     pub flag_link: bool,
+    /// Only known for protocol 0.13 and later
+    pub cardinality: Option<ShapeCardinality>,
     pub name: String,
     pub type_pos: TypePos,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BaseScalarTypeDescriptor {
     pub id: Uuid,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScalarTypeDescriptor {
     pub id: Uuid,
     pub base_type_pos: TypePos,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TupleTypeDescriptor {
     pub id: Uuid,
     pub element_types: Vec<TypePos>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NamedTupleTypeDescriptor {
     pub id: Uuid,
     pub elements: Vec<TupleElement>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TupleElement {
     pub name: String,
     pub type_pos: TypePos,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArrayTypeDescriptor {
     pub id: Uuid,
     pub type_pos: TypePos,
@@ -100,12 +124,14 @@ pub struct ArrayTypeDescriptor {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnumerationTypeDescriptor {
     pub id: Uuid,
     pub members: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature="with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TypeAnnotationDescriptor {
     pub annotated_type: u8,
     pub id: Uuid,
@@ -122,9 +148,26 @@ impl OutputTypedesc {
     pub fn build_codec(&self) -> Result<Arc<dyn Codec>, CodecError> {
         build_codec(self.root_pos(), self.descriptors())
     }
+    pub fn build_codec_with_limits(&self, limits: &Limits)
+        -> Result<Arc<dyn Codec>, CodecError>
+    {
+        build_codec_with_limits(self.root_pos(), self.descriptors(), limits)
+    }
     pub fn root_pos(&self) -> Option<TypePos> {
         self.root_pos
     }
+    pub fn id(&self) -> &Uuid {
+        &self.root_id
+    }
+    pub fn partial_decoder<T: queryable::ShapeFields>(&self)
+        -> Result<queryable::PartialDecoder<T>, queryable::DescriptorMismatch>
+    {
+        let root_pos = self.root_pos()
+            .ok_or(queryable::DescriptorMismatch::Expected {
+                expected: "object".into(),
+            })?;
+        queryable::PartialDecoder::new(root_pos, self.descriptors())
+    }
 }
 
 impl InputTypedesc {
@@ -134,9 +177,18 @@ impl InputTypedesc {
     pub fn build_codec(&self) -> Result<Arc<dyn Codec>, CodecError> {
         build_input_codec(Some(self.root_pos()), self.descriptors())
     }
+    pub fn build_codec_with_limits(&self, limits: &Limits)
+        -> Result<Arc<dyn Codec>, CodecError>
+    {
+        build_input_codec_with_limits(Some(self.root_pos()),
+                                      self.descriptors(), limits)
+    }
     pub fn root_pos(&self) -> TypePos {
         self.root_pos
     }
+    pub fn id(&self) -> &Uuid {
+        &self.root_id
+    }
     pub fn root(&self) -> &Descriptor {
         &self.array[self.root_pos.0 as usize]
     }
@@ -151,6 +203,20 @@ impl InputTypedesc {
             _ => false,
         }
     }
+    /// Checks arguments against descriptor before encoding
+    ///
+    /// This is an opt-in check (encoding validates values too), but unlike
+    /// the codec it reports the path of the offending argument, like
+    /// `$user.tags[2]`, and the full expected type. It's meant to be
+    /// enabled in development builds.
+    pub fn validate_args(&self, args: &Value) -> Result<(), EncodeError> {
+        self.validate_args_with_limits(args, &Limits::default())
+    }
+    pub fn validate_args_with_limits(&self, args: &Value, limits: &Limits)
+        -> Result<(), EncodeError>
+    {
+        validate(self.descriptors(), limits, self.root_pos(), "", args)
+    }
 }
 
 impl Descriptor {
@@ -169,17 +235,18 @@ impl Descriptor {
         }
     }
     pub fn decode(buf: &mut Cursor<Bytes>) -> Result<Descriptor, DecodeError> {
-        <Descriptor as Decode>::decode(buf)
+        Descriptor::decode_with_version(buf, &ProtocolVersion::current())
     }
-}
-
-impl Decode for Descriptor {
-    fn decode(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
+    pub fn decode_with_version(buf: &mut Cursor<Bytes>,
+                               proto: &ProtocolVersion)
+        -> Result<Descriptor, DecodeError>
+    {
         use Descriptor as D;
         ensure!(buf.remaining() >= 1, errors::Underflow);
         match buf.bytes()[0] {
             0 => SetDescriptor::decode(buf).map(D::Set),
-            1 => ObjectShapeDescriptor::decode(buf).map(D::ObjectShape),
+            1 => ObjectShapeDescriptor::decode(buf, proto)
+                .map(D::ObjectShape),
             2 => BaseScalarTypeDescriptor::decode(buf).map(D::BaseScalar),
             3 => ScalarTypeDescriptor::decode(buf).map(D::Scalar),
             4 => TupleTypeDescriptor::decode(buf).map(D::Tuple),
@@ -204,24 +271,40 @@ impl Decode for SetDescriptor {
     }
 }
 
-impl Decode for ObjectShapeDescriptor {
+impl Decode for Descriptor {
     fn decode(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
+        Descriptor::decode(buf)
+    }
+}
+
+impl ObjectShapeDescriptor {
+    fn decode(buf: &mut Cursor<Bytes>, proto: &ProtocolVersion)
+        -> Result<Self, DecodeError>
+    {
         ensure!(buf.remaining() >= 19, errors::Underflow);
         assert!(buf.get_u8() == 1);
         let id = Uuid::decode(buf)?;
         let element_count = buf.get_u16();
         let mut elements = Vec::with_capacity(element_count as usize);
         for _ in 0..element_count {
-            elements.push(ShapeElement::decode(buf)?);
+            elements.push(ShapeElement::decode(buf, proto)?);
         }
         Ok(ObjectShapeDescriptor { id, elements })
     }
 }
 
-impl Decode for ShapeElement {
-    fn decode(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
-        ensure!(buf.remaining() >= 7, errors::Underflow);
-        let flags = buf.get_u8();
+impl ShapeElement {
+    fn decode(buf: &mut Cursor<Bytes>, proto: &ProtocolVersion)
+        -> Result<Self, DecodeError>
+    {
+        let (flags, cardinality) = if proto.has_shape_cardinality() {
+            ensure!(buf.remaining() >= 11, errors::Underflow);
+            let flags = buf.get_u32();
+            (flags, Some(decode_cardinality(buf.get_u8())?))
+        } else {
+            ensure!(buf.remaining() >= 7, errors::Underflow);
+            (buf.get_u8() as u32, None)
+        };
         let name = String::decode(buf)?;
         ensure!(buf.remaining() >= 2, errors::Underflow);
         let type_pos = TypePos(buf.get_u16());
@@ -229,12 +312,25 @@ impl Decode for ShapeElement {
             flag_implicit: flags & 0b001 != 0,
             flag_link_property: flags & 0b010 != 0,
             flag_link: flags & 0b100 != 0,
+            cardinality,
             name,
             type_pos,
         })
     }
 }
 
+fn decode_cardinality(value: u8) -> Result<ShapeCardinality, DecodeError> {
+    use ShapeCardinality::*;
+    match value {
+        0x6e => Ok(NoResult),
+        0x6f => Ok(AtMostOne),
+        0x41 => Ok(One),
+        0x6d => Ok(Many),
+        0x4d => Ok(AtLeastOne),
+        cardinality => errors::InvalidCardinality { cardinality }.fail(),
+    }
+}
+
 impl Decode for BaseScalarTypeDescriptor {
     fn decode(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
         assert!(buf.get_u8() == 2);
@@ -93,10 +93,9 @@ impl Decode for Bytes {
 impl Decode for Uuid {
     fn decode(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
         ensure!(buf.remaining() >= 16, errors::Underflow);
-        let result = Uuid::from_slice(&buf.bytes()[..16])
-            .context(errors::InvalidUuid)?;
-        buf.advance(16);
-        Ok(result)
+        let mut bytes = [0u8; 16];
+        buf.copy_to_slice(&mut bytes);
+        Ok(Uuid::from_bytes(bytes))
     }
 }
 
@@ -0,0 +1,187 @@
+//! Classes of errors returned by the server
+//!
+//! Error codes are hierarchical: each byte of the code (from the most
+//! significant one) narrows down the class, and trailing zero bytes mean
+//! "any error in this class". So `ConstraintViolationError` (0x05020001) is
+//! also an `IntegrityError` (0x05020000) and an `ExecutionError`
+//! (0x05000000).
+//!
+//! ```rust
+//! # use edgedb_protocol::error_kinds::{ErrorKind, IntegrityError};
+//! # use edgedb_protocol::error_kinds::ConstraintViolationError;
+//! assert!(IntegrityError::contains(ConstraintViolationError::CODE));
+//! ```
+use bitflags::bitflags;
+
+
+bitflags! {
+    /// Properties of the error class that tell how to handle the error
+    pub struct Tags: u32 {
+        /// Transaction can be retried from the start
+        const SHOULD_RETRY = 0x1;
+        /// Connection is unusable and should be established again
+        const SHOULD_RECONNECT = 0x2;
+    }
+}
+
+/// A class of errors, implemented by marker types in this module
+pub trait ErrorKind {
+    const CODE: u32;
+    const NAME: &'static str;
+    /// Returns true if `code` belongs to this class or any of its subclasses
+    fn contains(code: u32) -> bool {
+        is_subclass(code, Self::CODE)
+    }
+}
+
+macro_rules! define_errors {
+    ($( $name:ident = $code:literal, [$($tag:ident),*]; )*) => {
+        $(
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            pub struct $name;
+
+            impl ErrorKind for $name {
+                const CODE: u32 = $code;
+                const NAME: &'static str = stringify!($name);
+            }
+        )*
+
+        fn lookup(code: u32) -> Option<(&'static str, Tags)> {
+            match code {
+                $(
+                    $code => Some((stringify!($name),
+                                   Tags::empty() $(| Tags::$tag)*)),
+                )*
+                _ => None,
+            }
+        }
+    }
+}
+
+define_errors! {
+    InternalServerError = 0x_01_00_00_00, [];
+    UnsupportedFeatureError = 0x_02_00_00_00, [];
+    ProtocolError = 0x_03_00_00_00, [];
+    BinaryProtocolError = 0x_03_01_00_00, [];
+    UnsupportedProtocolVersionError = 0x_03_01_00_01, [];
+    TypeSpecNotFoundError = 0x_03_01_00_02, [];
+    UnexpectedMessageError = 0x_03_01_00_03, [];
+    InputDataError = 0x_03_02_00_00, [];
+    ResultCardinalityMismatchError = 0x_03_03_00_00, [];
+    QueryError = 0x_04_00_00_00, [];
+    InvalidSyntaxError = 0x_04_01_00_00, [];
+    EdgeQLSyntaxError = 0x_04_01_01_00, [];
+    SchemaSyntaxError = 0x_04_01_02_00, [];
+    GraphQLSyntaxError = 0x_04_01_03_00, [];
+    InvalidTypeError = 0x_04_02_00_00, [];
+    InvalidTargetError = 0x_04_02_01_00, [];
+    InvalidLinkTargetError = 0x_04_02_01_01, [];
+    InvalidPropertyTargetError = 0x_04_02_01_02, [];
+    InvalidReferenceError = 0x_04_03_00_00, [];
+    UnknownModuleError = 0x_04_03_00_01, [];
+    UnknownLinkError = 0x_04_03_00_02, [];
+    UnknownPropertyError = 0x_04_03_00_03, [];
+    UnknownUserError = 0x_04_03_00_04, [];
+    UnknownDatabaseError = 0x_04_03_00_05, [];
+    UnknownParameterError = 0x_04_03_00_06, [];
+    SchemaError = 0x_04_04_00_00, [];
+    SchemaDefinitionError = 0x_04_05_00_00, [];
+    InvalidDefinitionError = 0x_04_05_01_00, [];
+    InvalidModuleDefinitionError = 0x_04_05_01_01, [];
+    InvalidLinkDefinitionError = 0x_04_05_01_02, [];
+    InvalidPropertyDefinitionError = 0x_04_05_01_03, [];
+    InvalidUserDefinitionError = 0x_04_05_01_04, [];
+    InvalidDatabaseDefinitionError = 0x_04_05_01_05, [];
+    InvalidOperatorDefinitionError = 0x_04_05_01_06, [];
+    InvalidViewDefinitionError = 0x_04_05_01_07, [];
+    InvalidFunctionDefinitionError = 0x_04_05_01_08, [];
+    InvalidConstraintDefinitionError = 0x_04_05_01_09, [];
+    InvalidCastDefinitionError = 0x_04_05_01_0A, [];
+    DuplicateDefinitionError = 0x_04_05_02_00, [];
+    DuplicateModuleDefinitionError = 0x_04_05_02_01, [];
+    DuplicateLinkDefinitionError = 0x_04_05_02_02, [];
+    DuplicatePropertyDefinitionError = 0x_04_05_02_03, [];
+    DuplicateUserDefinitionError = 0x_04_05_02_04, [];
+    DuplicateDatabaseDefinitionError = 0x_04_05_02_05, [];
+    DuplicateOperatorDefinitionError = 0x_04_05_02_06, [];
+    DuplicateViewDefinitionError = 0x_04_05_02_07, [];
+    DuplicateFunctionDefinitionError = 0x_04_05_02_08, [];
+    DuplicateConstraintDefinitionError = 0x_04_05_02_09, [];
+    DuplicateCastDefinitionError = 0x_04_05_02_0A, [];
+    QueryTimeoutError = 0x_04_06_00_00, [];
+    ExecutionError = 0x_05_00_00_00, [];
+    InvalidValueError = 0x_05_01_00_00, [];
+    DivisionByZeroError = 0x_05_01_00_01, [];
+    NumericOutOfRangeError = 0x_05_01_00_02, [];
+    IntegrityError = 0x_05_02_00_00, [];
+    ConstraintViolationError = 0x_05_02_00_01, [];
+    CardinalityViolationError = 0x_05_02_00_02, [];
+    MissingRequiredError = 0x_05_02_00_03, [];
+    TransactionError = 0x_05_03_00_00, [];
+    TransactionSerializationError = 0x_05_03_00_01, [SHOULD_RETRY];
+    TransactionDeadlockError = 0x_05_03_00_02, [SHOULD_RETRY];
+    ConfigurationError = 0x_06_00_00_00, [];
+    AccessError = 0x_07_00_00_00, [];
+    AuthenticationError = 0x_07_01_00_00, [];
+    LogMessage = 0x_F0_00_00_00, [];
+    WarningMessage = 0x_F0_01_00_00, [];
+    ClientError = 0x_FF_00_00_00, [];
+    ClientConnectionError = 0x_FF_01_00_00, [SHOULD_RECONNECT];
+    InterfaceError = 0x_FF_02_00_00, [];
+    QueryArgumentError = 0x_FF_02_01_00, [];
+    MissingArgumentError = 0x_FF_02_01_01, [];
+    UnknownArgumentError = 0x_FF_02_01_02, [];
+    NoDataError = 0x_FF_03_00_00, [];
+}
+
+/// Returns true if error `code` is `class` or one of its subclasses
+pub fn is_subclass(code: u32, class: u32) -> bool {
+    let mask = match class.trailing_zeros() / 8 {
+        0 => 0xFFFF_FFFF,
+        1 => 0xFFFF_FF00,
+        2 => 0xFFFF_0000,
+        3 => 0xFF00_0000,
+        _ => 0,
+    };
+    code & mask == class
+}
+
+/// Returns name of the error class, if code is known
+pub fn name(code: u32) -> Option<&'static str> {
+    lookup(code).map(|(name, _)| name)
+}
+
+/// Returns tags of the error class
+///
+/// Unknown codes have no tags.
+pub fn tags(code: u32) -> Tags {
+    lookup(code).map(|(_, tags)| tags).unwrap_or_else(Tags::empty)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hierarchy() {
+        assert!(ExecutionError::contains(ConstraintViolationError::CODE));
+        assert!(IntegrityError::contains(ConstraintViolationError::CODE));
+        assert!(ConstraintViolationError::contains(
+            ConstraintViolationError::CODE));
+        assert!(!ConstraintViolationError::contains(IntegrityError::CODE));
+        assert!(!QueryError::contains(ConstraintViolationError::CODE));
+        assert!(!InvalidDefinitionError::contains(
+            DuplicateDefinitionError::CODE));
+        assert!(SchemaDefinitionError::contains(
+            DuplicateCastDefinitionError::CODE));
+    }
+
+    #[test]
+    fn names_and_tags() {
+        assert_eq!(name(0x_05_03_00_02), Some("TransactionDeadlockError"));
+        assert_eq!(name(0x_05_03_00_FF), None);
+        assert!(tags(TransactionSerializationError::CODE)
+            .contains(Tags::SHOULD_RETRY));
+        assert!(tags(ConstraintViolationError::CODE).is_empty());
+    }
+}
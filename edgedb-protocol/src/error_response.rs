@@ -6,6 +6,7 @@ use std::collections::BTreeMap;
 use bytes::Bytes;
 
 pub use crate::server_message::{ErrorSeverity, ErrorResponse};
+use crate::error_kinds::{self, ErrorKind, Tags};
 
 pub const FIELD_HINT: u16 = 0x_00_01;
 pub const FIELD_DETAILS: u16 = 0x_00_02;
@@ -17,6 +18,21 @@ pub const FIELD_POSITION_END: u16 = 0x_FF_F2;
 pub const FIELD_LINE: u16 = 0x_FF_F3;
 pub const FIELD_COLUMN: u16 = 0x_FF_F4;
 
+/// Location of the error in the query text
+///
+/// Any field is `None` if the server didn't send it or it is malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position {
+    /// Character offset of the start of the span
+    pub start: Option<usize>,
+    /// Character offset of the end of the span
+    pub end: Option<usize>,
+    /// Line number, starting from 1
+    pub line: Option<usize>,
+    /// Column number, starting from 1
+    pub column: Option<usize>,
+}
+
 pub struct DisplayError<'a>(&'a ErrorResponse, bool);
 pub struct VerboseError<'a>(&'a ErrorResponse);
 
@@ -29,6 +45,39 @@ impl ErrorResponse {
     pub fn display_verbose(&self) -> VerboseError {
         VerboseError(self)
     }
+    pub fn hint(&self) -> Option<&str> {
+        text_attr(self, FIELD_HINT)
+    }
+    pub fn details(&self) -> Option<&str> {
+        text_attr(self, FIELD_DETAILS)
+    }
+    pub fn server_traceback(&self) -> Option<&str> {
+        text_attr(self, FIELD_SERVER_TRACEBACK)
+    }
+    /// Returns position of the error in the query
+    ///
+    /// Returns `None` if none of the position attributes is present.
+    pub fn position(&self) -> Option<Position> {
+        let position = Position {
+            start: num_attr(self, FIELD_POSITION_START),
+            end: num_attr(self, FIELD_POSITION_END),
+            line: num_attr(self, FIELD_LINE),
+            column: num_attr(self, FIELD_COLUMN),
+        };
+        if position == Position::default() {
+            None
+        } else {
+            Some(position)
+        }
+    }
+}
+
+fn text_attr(e: &ErrorResponse, field: u16) -> Option<&str> {
+    e.attributes.get(&field).and_then(|x| str::from_utf8(x).ok())
+}
+
+fn num_attr(e: &ErrorResponse, field: u16) -> Option<usize> {
+    text_attr(e, field).and_then(|x| x.parse().ok())
 }
 
 pub fn severity_marker(code: ErrorSeverity) -> &'static str {
@@ -41,81 +90,21 @@ pub fn severity_marker(code: ErrorSeverity) -> &'static str {
 }
 
 pub fn error_name(code: u32) -> &'static str {
-    match code {
-        0x_01_00_00_00 => "InternalServerError",
-        0x_02_00_00_00 => "UnsupportedFeatureError",
-        0x_03_00_00_00 => "ProtocolError",
-        0x_03_01_00_00 => "BinaryProtocolError",
-        0x_03_01_00_01 => "UnsupportedProtocolVersionError",
-        0x_03_01_00_02 => "TypeSpecNotFoundError",
-        0x_03_01_00_03 => "UnexpectedMessageError",
-        0x_03_02_00_00 => "InputDataError",
-        0x_03_03_00_00 => "ResultCardinalityMismatchError",
-        0x_04_00_00_00 => "QueryError",
-        0x_04_01_00_00 => "InvalidSyntaxError",
-        0x_04_01_01_00 => "EdgeQLSyntaxError",
-        0x_04_01_02_00 => "SchemaSyntaxError",
-        0x_04_01_03_00 => "GraphQLSyntaxError",
-        0x_04_02_00_00 => "InvalidTypeError",
-        0x_04_02_01_00 => "InvalidTargetError",
-        0x_04_02_01_01 => "InvalidLinkTargetError",
-        0x_04_02_01_02 => "InvalidPropertyTargetError",
-        0x_04_03_00_00 => "InvalidReferenceError",
-        0x_04_03_00_01 => "UnknownModuleError",
-        0x_04_03_00_02 => "UnknownLinkError",
-        0x_04_03_00_03 => "UnknownPropertyError",
-        0x_04_03_00_04 => "UnknownUserError",
-        0x_04_03_00_05 => "UnknownDatabaseError",
-        0x_04_03_00_06 => "UnknownParameterError",
-        0x_04_04_00_00 => "SchemaError",
-        0x_04_05_00_00 => "SchemaDefinitionError",
-        0x_04_05_01_00 => "InvalidDefinitionError",
-        0x_04_05_01_01 => "InvalidModuleDefinitionError",
-        0x_04_05_01_02 => "InvalidLinkDefinitionError",
-        0x_04_05_01_03 => "InvalidPropertyDefinitionError",
-        0x_04_05_01_04 => "InvalidUserDefinitionError",
-        0x_04_05_01_05 => "InvalidDatabaseDefinitionError",
-        0x_04_05_01_06 => "InvalidOperatorDefinitionError",
-        0x_04_05_01_07 => "InvalidViewDefinitionError",
-        0x_04_05_01_08 => "InvalidFunctionDefinitionError",
-        0x_04_05_01_09 => "InvalidConstraintDefinitionError",
-        0x_04_05_01_0A => "InvalidCastDefinitionError",
-        0x_04_05_02_00 => "DuplicateDefinitionError",
-        0x_04_05_02_01 => "DuplicateModuleDefinitionError",
-        0x_04_05_02_02 => "DuplicateLinkDefinitionError",
-        0x_04_05_02_03 => "DuplicatePropertyDefinitionError",
-        0x_04_05_02_04 => "DuplicateUserDefinitionError",
-        0x_04_05_02_05 => "DuplicateDatabaseDefinitionError",
-        0x_04_05_02_06 => "DuplicateOperatorDefinitionError",
-        0x_04_05_02_07 => "DuplicateViewDefinitionError",
-        0x_04_05_02_08 => "DuplicateFunctionDefinitionError",
-        0x_04_05_02_09 => "DuplicateConstraintDefinitionError",
-        0x_04_05_02_0A => "DuplicateCastDefinitionError",
-        0x_04_06_00_00 => "QueryTimeoutError",
-        0x_05_00_00_00 => "ExecutionError",
-        0x_05_01_00_00 => "InvalidValueError",
-        0x_05_01_00_01 => "DivisionByZeroError",
-        0x_05_01_00_02 => "NumericOutOfRangeError",
-        0x_05_02_00_00 => "IntegrityError",
-        0x_05_02_00_01 => "ConstraintViolationError",
-        0x_05_02_00_02 => "CardinalityViolationError",
-        0x_05_02_00_03 => "MissingRequiredError",
-        0x_05_03_00_00 => "TransactionError",
-        0x_05_03_00_01 => "TransactionSerializationError",
-        0x_05_03_00_02 => "TransactionDeadlockError",
-        0x_06_00_00_00 => "ConfigurationError",
-        0x_07_00_00_00 => "AccessError",
-        0x_07_01_00_00 => "AuthenticationError",
-        0x_F0_00_00_00 => "LogMessage",
-        0x_F0_01_00_00 => "WarningMessage",
-        0x_FF_00_00_00 => "ClientError",
-        0x_FF_01_00_00 => "ClientConnectionError",
-        0x_FF_02_00_00 => "InterfaceError",
-        0x_FF_02_01_00 => "QueryArgumentError",
-        0x_FF_02_01_01 => "MissingArgumentError",
-        0x_FF_02_01_02 => "UnknownArgumentError",
-        0x_FF_03_00_00 => "NoDataError",
-        _ => "UnknownError",
+    error_kinds::name(code).unwrap_or("UnknownError")
+}
+
+impl ErrorResponse {
+    /// Returns true if error belongs to the class `T` or its subclass
+    ///
+    /// ```rust,ignore
+    /// if err.is::<TransactionError>() { /* ... */ }
+    /// ```
+    pub fn is<T: ErrorKind>(&self) -> bool {
+        T::contains(self.code)
+    }
+    /// Returns true if error class has all of the `tags`
+    pub fn has_tag(&self, tags: Tags) -> bool {
+        error_kinds::tags(self.code).contains(tags)
     }
 }
 
@@ -134,24 +123,17 @@ impl fmt::Display for DisplayError<'_> {
             severity_marker(e.severity),
             error_name(e.code),
             e.message)?;
-        if let Some(hint) = e.attributes.get(&FIELD_HINT) {
-            if let Ok(hint) = str::from_utf8(hint) {
-                write!(f, "\n  Hint: {}", hint)?;
-            }
+        if let Some(hint) = e.hint() {
+            write!(f, "\n  Hint: {}", hint)?;
         }
-        if let Some(detail) = e.attributes.get(&FIELD_DETAILS) {
-            if let Ok(detail) = str::from_utf8(detail) {
-                write!(f, "\n  Detail: {}", detail)?;
-            }
+        if let Some(detail) = e.details() {
+            write!(f, "\n  Detail: {}", detail)?;
         }
         if e.code == 0x_01_00_00_00 || *verbose {
-            let tb = e.attributes.get(&FIELD_SERVER_TRACEBACK);
-            if let Some(traceback) = tb {
-                if let Ok(traceback) = str::from_utf8(traceback) {
-                    write!(f, "\n  Server traceback:")?;
-                    for line in traceback.lines() {
-                        write!(f, "\n      {}", line)?;
-                    }
+            if let Some(traceback) = e.server_traceback() {
+                write!(f, "\n  Server traceback:")?;
+                for line in traceback.lines() {
+                    write!(f, "\n      {}", line)?;
                 }
             }
         }
@@ -221,3 +203,43 @@ impl fmt::Display for VerboseError<'_> {
         Ok(())
     }
 }
+
+/// Serializes as `{code, name, message, hint, details, position}`
+///
+/// `hint` and `details` are `null` when not provided by the server,
+/// `position` is either `null` or an object with optional `start`, `end`,
+/// `line` and `column` fields. Server traceback and unknown attributes are
+/// not included.
+#[cfg(feature="serde")]
+impl serde::Serialize for ErrorResponse {
+    fn serialize<S: serde::Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("ErrorResponse", 6)?;
+        s.serialize_field("code", &self.code)?;
+        s.serialize_field("name", error_name(self.code))?;
+        s.serialize_field("message", &self.message)?;
+        s.serialize_field("hint", &self.hint())?;
+        s.serialize_field("details", &self.details())?;
+        s.serialize_field("position", &self.position())?;
+        s.end()
+    }
+}
+
+#[cfg(feature="serde")]
+impl serde::Serialize for Position {
+    fn serialize<S: serde::Serializer>(&self, serializer: S)
+        -> Result<S::Ok, S::Error>
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("Position", 4)?;
+        s.serialize_field("start", &self.start)?;
+        s.serialize_field("end", &self.end)?;
+        s.serialize_field("line", &self.line)?;
+        s.serialize_field("column", &self.column)?;
+        s.end()
+    }
+}
@@ -56,6 +56,15 @@ pub enum DecodeError {
     TooManyDescriptors { backtrace: Backtrace, index: usize },
     #[snafu(display("uuid {} not found", uuid))]
     UuidNotFound { backtrace: Backtrace, uuid: uuid::Uuid },
+    #[snafu(display("number has {} digits, the limit is {}",
+                    digits, max_digits))]
+    TooManyDigits { backtrace: Backtrace, digits: usize, max_digits: usize },
+    #[snafu(display("error deserializing json: {}", source))]
+    InvalidJson { backtrace: Backtrace,
+                  source: Box<dyn std::error::Error + Send + Sync> },
+    #[snafu(display("negative duration can't be decoded as \
+                     std::time::Duration"))]
+    NegativeDuration { backtrace: Backtrace },
 }
 
 #[derive(Snafu, Debug)]
@@ -100,6 +109,12 @@ pub enum EncodeError {
     TupleShapeMismatch { backtrace: Backtrace },
     #[snafu(display("enum value is not in type descriptor"))]
     MissingEnumValue { backtrace: Backtrace },
+    #[snafu(display("invalid type of {}: expected {}, got {}",
+                    path, expected, value_type))]
+    ArgumentMismatch { backtrace: Backtrace, path: String,
+                       expected: String, value_type: &'static str },
+    #[snafu(display("arguments are nested deeper than {}", max_depth))]
+    ArgumentsTooDeep { backtrace: Backtrace, max_depth: usize },
 }
 
 #[derive(Snafu, Debug)]
@@ -110,6 +125,9 @@ pub enum CodecError {
     UnexpectedTypePos { backtrace: Backtrace, position: u16 },
     #[snafu(display("base scalar with uuid {} not found", uuid))]
     UndefinedBaseScalar { backtrace: Backtrace, uuid: uuid::Uuid },
+    #[snafu(display("type descriptors are nested deeper than {}",
+                    max_depth))]
+    TooDeep { backtrace: Backtrace, max_depth: usize },
 }
 
 pub fn invalid_value(codec: &'static str, value: &Value) -> EncodeError
@@ -0,0 +1,55 @@
+/// Version of the binary protocol
+///
+/// Messages and descriptors are decoded in the format of
+/// `ProtocolVersion::current()` unless a different version is specified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProtocolVersion {
+    pub(crate) major_ver: u16,
+    pub(crate) minor_ver: u16,
+}
+
+impl ProtocolVersion {
+    pub fn current() -> ProtocolVersion {
+        ProtocolVersion {
+            major_ver: 0,
+            minor_ver: 7,
+        }
+    }
+    pub fn new(major_ver: u16, minor_ver: u16) -> ProtocolVersion {
+        ProtocolVersion { major_ver, minor_ver }
+    }
+    pub fn version_tuple(&self) -> (u16, u16) {
+        (self.major_ver, self.minor_ver)
+    }
+    pub fn is_at_least(&self, major_ver: u16, minor_ver: u16) -> bool {
+        self.major_ver > major_ver ||
+            self.major_ver == major_ver && self.minor_ver >= minor_ver
+    }
+    pub fn is_1(&self) -> bool {
+        self.major_ver >= 1
+    }
+    /// Object shape elements carry cardinality
+    pub fn has_shape_cardinality(&self) -> bool {
+        self.is_at_least(0, 13)
+    }
+    /// Session state is sent with each `Execute1` instead of being kept
+    /// on the server
+    pub fn supports_inline_state(&self) -> bool {
+        self.is_1()
+    }
+    /// Query is parsed and executed with a single `Execute1` message
+    pub fn supports_execute1(&self) -> bool {
+        self.is_1()
+    }
+    /// Allowed capabilities are a field of the message rather than a
+    /// header
+    pub fn has_capabilities_field(&self) -> bool {
+        self.is_1()
+    }
+}
+
+impl Default for ProtocolVersion {
+    fn default() -> ProtocolVersion {
+        ProtocolVersion::current()
+    }
+}
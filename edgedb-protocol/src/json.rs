@@ -0,0 +1,236 @@
+//! Helpers for results returned in JSON format
+//!
+//! Key casing can be converted on the fly: data is scanned once and only
+//! object keys are rewritten, the rest of the document is copied verbatim
+//! without parsing values.
+
+/// Target casing for object keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCase {
+    /// `first_name` → `firstName`
+    Camel,
+    /// `firstName` → `first_name`
+    Snake,
+}
+
+/// Streaming converter of object key casing
+///
+/// Input may be split into chunks at any byte. Input is expected to be
+/// valid JSON, malformed input is not detected and is passed through with
+/// unspecified key conversion.
+#[derive(Debug)]
+pub struct KeyCaseTransform {
+    case: KeyCase,
+    stack: Vec<u8>,
+    expect_key: bool,
+    in_string: bool,
+    in_key: bool,
+    escape: bool,
+    key: Vec<u8>,
+}
+
+impl KeyCaseTransform {
+    pub fn new(case: KeyCase) -> KeyCaseTransform {
+        KeyCaseTransform {
+            case,
+            stack: Vec::new(),
+            expect_key: false,
+            in_string: false,
+            in_key: false,
+            escape: false,
+            key: Vec::new(),
+        }
+    }
+    /// Converts next chunk of input, appending result to `output`
+    ///
+    /// A key split between chunks is written out when it's complete.
+    pub fn push(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        output.reserve(input.len());
+        for &b in input {
+            if self.in_string {
+                if self.escape {
+                    self.escape = false;
+                } else if b == b'\\' {
+                    self.escape = true;
+                } else if b == b'"' {
+                    self.in_string = false;
+                    if self.in_key {
+                        self.in_key = false;
+                        convert_key(&self.key, self.case, output);
+                        self.key.clear();
+                    }
+                    output.push(b);
+                    continue;
+                }
+                if self.in_key {
+                    self.key.push(b);
+                } else {
+                    output.push(b);
+                }
+                continue;
+            }
+            match b {
+                b'{' => {
+                    self.stack.push(b);
+                    self.expect_key = true;
+                }
+                b'[' => {
+                    self.stack.push(b);
+                    self.expect_key = false;
+                }
+                b'}' | b']' => {
+                    self.stack.pop();
+                    self.expect_key = false;
+                }
+                b',' => {
+                    self.expect_key = self.stack.last() == Some(&b'{');
+                }
+                b':' => {
+                    self.expect_key = false;
+                }
+                b'"' => {
+                    self.in_string = true;
+                    self.in_key = self.expect_key;
+                    self.expect_key = false;
+                }
+                _ => {}
+            }
+            output.push(b);
+        }
+    }
+}
+
+/// Converts keys of a complete JSON document
+pub fn convert_keys(json: &str, case: KeyCase) -> String {
+    let mut output = Vec::with_capacity(json.len());
+    KeyCaseTransform::new(case).push(json.as_bytes(), &mut output);
+    // only ASCII letters and underscores are inserted or removed, so
+    // output is valid UTF-8 if input is
+    String::from_utf8(output).expect("valid utf-8")
+}
+
+fn convert_key(key: &[u8], case: KeyCase, output: &mut Vec<u8>) {
+    // leading and trailing underscores are kept, as in `__tid__`
+    let start = key.iter().position(|&c| c != b'_').unwrap_or(key.len());
+    let end = key.iter().rposition(|&c| c != b'_').map(|p| p + 1)
+        .unwrap_or(start);
+    output.extend_from_slice(&key[..start]);
+    let body = &key[start..end];
+    match case {
+        KeyCase::Camel => {
+            let mut upper = false;
+            let mut idx = 0;
+            while idx < body.len() {
+                let c = body[idx];
+                if c == b'\\' {
+                    let len = escape_len(&body[idx..]);
+                    output.extend_from_slice(&body[idx..idx+len]);
+                    idx += len;
+                    upper = false;
+                    continue;
+                }
+                idx += 1;
+                if c == b'_' {
+                    upper = true;
+                } else if upper {
+                    output.push(c.to_ascii_uppercase());
+                    upper = false;
+                } else {
+                    output.push(c);
+                }
+            }
+        }
+        KeyCase::Snake => {
+            // escaped characters are never converted and don't count as
+            // a previous character for word boundaries
+            let mut prev: Option<u8> = None;
+            let mut idx = 0;
+            while idx < body.len() {
+                let c = body[idx];
+                if c == b'\\' {
+                    let len = escape_len(&body[idx..]);
+                    output.extend_from_slice(&body[idx..idx+len]);
+                    idx += len;
+                    prev = None;
+                    continue;
+                }
+                if c.is_ascii_uppercase() {
+                    let next = body.get(idx+1);
+                    let boundary = match prev {
+                        Some(p) if p.is_ascii_lowercase()
+                                || p.is_ascii_digit() => true,
+                        Some(p) if p.is_ascii_uppercase() => {
+                            next.map(|n| n.is_ascii_lowercase())
+                                .unwrap_or(false)
+                        }
+                        _ => false,
+                    };
+                    if boundary {
+                        output.push(b'_');
+                    }
+                    output.push(c.to_ascii_lowercase());
+                } else {
+                    output.push(c);
+                }
+                prev = Some(c);
+                idx += 1;
+            }
+        }
+    }
+    output.extend_from_slice(&key[end..]);
+}
+
+/// Length of the escape sequence at the start of `data`, such as `\"` or
+/// `\u00e9`
+fn escape_len(data: &[u8]) -> usize {
+    let len = if data.get(1) == Some(&b'u') { 6 } else { 2 };
+    len.min(data.len())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{convert_keys, KeyCase, KeyCaseTransform};
+
+    #[test]
+    fn camel() {
+        assert_eq!(convert_keys(
+            r#"[{"first_name": "John", "__tid__": "x", "last_name": null}]"#,
+            KeyCase::Camel),
+            r#"[{"firstName": "John", "__tid__": "x", "lastName": null}]"#);
+        // values and nested arrays are not touched
+        assert_eq!(convert_keys(
+            r#"{"user_tags": ["snake_case", {"tag_name": "a,b"}]}"#,
+            KeyCase::Camel),
+            r#"{"userTags": ["snake_case", {"tagName": "a,b"}]}"#);
+    }
+
+    #[test]
+    fn snake() {
+        assert_eq!(convert_keys(
+            r#"{"firstName": "John", "HTTPServer": 1, "v2Api": {"x": 2}}"#,
+            KeyCase::Snake),
+            r#"{"first_name": "John", "http_server": 1, "v2_api": {"x": 2}}"#);
+        assert_eq!(convert_keys(r#"{"a\"bC": "dE"}"#, KeyCase::Snake),
+                   r#"{"a\"b_c": "dE"}"#);
+        // escaped characters are copied verbatim
+        assert_eq!(convert_keys(r#"{"\u00C9tatCivil": 1, "a\nB": 2}"#,
+                                KeyCase::Snake),
+                   r#"{"\u00C9tat_civil": 1, "a\nb": 2}"#);
+        assert_eq!(convert_keys(r#"{"\u00e9tat_civil": 1, "a_\u00e9": 2}"#,
+                                KeyCase::Camel),
+                   r#"{"\u00e9tatCivil": 1, "a\u00e9": 2}"#);
+    }
+
+    #[test]
+    fn chunked() {
+        let input = br#"{"first_name": {"nested_key": "value_x"}}"#;
+        for split in 0..input.len() {
+            let mut tr = KeyCaseTransform::new(KeyCase::Camel);
+            let mut output = Vec::new();
+            tr.push(&input[..split], &mut output);
+            tr.push(&input[split..], &mut output);
+            assert_eq!(&output[..],
+                       &br#"{"firstName": {"nestedKey": "value_x"}}"#[..]);
+        }
+    }
+}
@@ -1,10 +1,23 @@
 mod encoding;
 mod common;
+mod sealed;
+mod type_names;
 pub mod client_message;
 pub mod server_message;
 pub mod errors;
 pub mod error_response;
+pub mod error_kinds;
 pub mod descriptors;
 pub mod value;
 pub mod codec;
 pub mod queryable;
+pub mod replay;
+pub mod json;
+pub mod features;
+pub mod prelude;
+
+pub use codec::{Codec, Limits, build_codec, build_input_codec};
+pub use descriptors::{OutputTypedesc, InputTypedesc};
+pub use errors::{DecodeError, EncodeError, CodecError};
+pub use queryable::Queryable;
+pub use value::Value;
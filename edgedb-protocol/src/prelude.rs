@@ -0,0 +1,14 @@
+//! Commonly used traits and types
+//!
+//! ```rust
+//! use edgedb_protocol::prelude::*;
+//! ```
+//!
+//! Items are only added to the prelude, so glob-importing it is stable
+//! across releases even when modules are reorganized.
+pub use crate::codec::{Codec, Limits};
+pub use crate::descriptors::{OutputTypedesc, InputTypedesc};
+pub use crate::queryable::Queryable;
+pub use crate::value::{Value, Json};
+pub use crate::value::{BigInt, Decimal, Duration};
+pub use crate::value::{Datetime, LocalDatetime, LocalDate, LocalTime};
@@ -1,20 +1,34 @@
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
 use std::io::Cursor;
+use std::marker::PhantomData;
+use std::sync::Arc;
 
-use bytes::{Bytes, Buf};
+use bytes::{Bytes, Buf, BytesMut, BufMut};
 use snafu::{Snafu, ensure};
 use uuid::Uuid;
 
-use crate::errors::{self, DecodeError};
+use crate::errors::{self, DecodeError, CodecError};
 use crate::codec::raw::RawCodec;
-use crate::codec;
-use crate::descriptors::{Descriptor, TypePos};
+use crate::codec::{self, Codec, ObjectShape};
+use crate::descriptors::{Descriptor, TypePos, ObjectShapeDescriptor};
+use crate::type_names::{descriptor_name, rust_type};
+use crate::value::Value;
 
+pub mod duration;
+#[cfg(feature="with-serde")]
+pub mod json;
 
 #[derive(Snafu, Debug)]
 #[non_exhaustive]
 pub enum DescriptorMismatch {
-    #[snafu(display("unexpected type {}, expected {}", unexpected, expected))]
-    WrongType { unexpected: String, expected: String },
+    #[snafu(display("unexpected type {}, expected {}{}",
+                    unexpected, expected,
+                    suggestion.as_ref()
+                        .map(|s| format!(" (use {} to decode it)", s))
+                        .unwrap_or_default()))]
+    WrongType { unexpected: String, expected: String,
+                suggestion: Option<String> },
     #[snafu(display("unexpected field {}, expected {}", unexpected, expected))]
     WrongField { unexpected: String, expected: String },
     #[snafu(display("expected {} fields, got {}", expected, unexpected))]
@@ -23,6 +37,8 @@ pub enum DescriptorMismatch {
     Expected { expected: String },
     #[snafu(display("invalid type descriptor"))]
     InvalidDescriptor,
+    #[snafu(display("can't build codec for remaining fields: {}", source))]
+    RemainderCodec { source: CodecError },
 }
 
 pub struct DescriptorContext<'a> {
@@ -40,6 +56,27 @@ pub trait Queryable: Sized {
         -> Result<(), DescriptorMismatch>;
 }
 
+/// Implemented by types decoded from objects
+///
+/// Lists the names of shape elements the type consumes, in the order
+/// `check_descriptor` expects them (including implicit `__tid__` and `id`).
+pub trait ShapeFields: Queryable {
+    fn field_names() -> &'static [&'static str];
+}
+
+/// Decodes an object into a typed part and a dynamic remainder
+///
+/// Fields known to `T` are decoded with `Queryable`, all other elements of
+/// the shape are decoded into a `Value::Object` in their original order.
+#[derive(Debug)]
+pub struct PartialDecoder<T> {
+    total: usize,
+    known: Vec<usize>,
+    rest: Vec<(usize, Arc<dyn Codec>)>,
+    rest_shape: ObjectShape,
+    phantom: PhantomData<fn() -> T>,
+}
+
 impl DescriptorContext<'_> {
     pub(crate) fn new(descriptors: &[Descriptor]) -> DescriptorContext {
         DescriptorContext { descriptors }
@@ -54,9 +91,9 @@ impl DescriptorContext<'_> {
         -> DescriptorMismatch
     {
         DescriptorMismatch::WrongType {
-            // TODO(tailhook) human-readable type description
-            unexpected: format!("{:?}", descriptor),
+            unexpected: descriptor_name(self.descriptors, descriptor),
             expected: expected.into(),
+            suggestion: rust_type(self.descriptors, descriptor),
         }
     }
     pub fn field_number(&self, expected: usize, unexpected: usize)
@@ -166,3 +203,151 @@ impl Queryable for bool {
         Err(ctx.wrong_type(desc, "bool"))
     }
 }
+
+/// Sets decoded into `BTreeSet` are ordered regardless of the order in which
+/// the server sends elements, duplicates are merged
+impl<T: Queryable + Ord> Queryable for BTreeSet<T> {
+    fn decode_raw(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
+        ensure!(buf.remaining() >= 12, errors::Underflow);
+        let ndims = buf.get_u32();
+        let _reserved0 = buf.get_u32();
+        let _reserved1 = buf.get_u32();
+        let mut result = BTreeSet::new();
+        if ndims == 0 {
+            return Ok(result);
+        }
+        ensure!(ndims == 1, errors::InvalidSetShape);
+        ensure!(buf.remaining() >= 8, errors::Underflow);
+        let size = buf.get_u32() as usize;
+        let lower = buf.get_u32();
+        ensure!(lower == 1, errors::InvalidSetShape);
+        for _ in 0..size {
+            ensure!(buf.remaining() >= 4, errors::Underflow);
+            let len = buf.get_u32() as usize;
+            ensure!(buf.remaining() >= len, errors::Underflow);
+            let off = buf.position() as usize;
+            let mut chunk = Cursor::new(buf.get_ref().slice(off..off + len));
+            buf.advance(len);
+            result.insert(T::decode(&mut chunk)?);
+        }
+        Ok(result)
+    }
+    fn check_descriptor(ctx: &DescriptorContext, type_pos: TypePos)
+        -> Result<(), DescriptorMismatch>
+    {
+        let desc = ctx.get(type_pos)?;
+        match desc {
+            Descriptor::Set(set) => T::check_descriptor(ctx, set.type_pos),
+            _ => Err(ctx.wrong_type(desc, "set")),
+        }
+    }
+}
+
+impl<T: ShapeFields> PartialDecoder<T> {
+    pub fn new(root_pos: TypePos, descriptors: &[Descriptor])
+        -> Result<PartialDecoder<T>, DescriptorMismatch>
+    {
+        let ctx = DescriptorContext::new(descriptors);
+        let desc = ctx.get(root_pos)?;
+        let shape = match desc {
+            Descriptor::ObjectShape(shape) => shape,
+            _ => return Err(ctx.wrong_type(desc, "object")),
+        };
+        let mut known = Vec::with_capacity(T::field_names().len());
+        for name in T::field_names() {
+            let idx = shape.elements.iter().position(|el| el.name == *name)
+                .ok_or_else(|| ctx.expected(&format!("field {:?}", name)))?;
+            known.push(idx);
+        }
+
+        // Type is checked against a shape containing only its own fields
+        let mut subset = descriptors.to_vec();
+        let subset_pos = u16::try_from(subset.len()).ok().map(TypePos)
+            .ok_or(DescriptorMismatch::InvalidDescriptor)?;
+        subset.push(Descriptor::ObjectShape(ObjectShapeDescriptor {
+            id: shape.id,
+            elements: known.iter().map(|&i| shape.elements[i].clone())
+                .collect(),
+        }));
+        T::check_descriptor(&DescriptorContext::new(&subset), subset_pos)?;
+
+        let mut rest = Vec::new();
+        let mut rest_elements = Vec::new();
+        for (idx, el) in shape.elements.iter().enumerate() {
+            if known.contains(&idx) {
+                continue;
+            }
+            let codec = codec::build_codec(Some(el.type_pos), descriptors)
+                .map_err(|source| DescriptorMismatch::RemainderCodec {
+                    source,
+                })?;
+            rest.push((idx, codec));
+            rest_elements.push(el.clone());
+        }
+        Ok(PartialDecoder {
+            total: shape.elements.len(),
+            known,
+            rest,
+            rest_shape: rest_elements.as_slice().into(),
+            phantom: PhantomData,
+        })
+    }
+    /// Shape of the object returned as a remainder
+    pub fn rest_shape(&self) -> &ObjectShape {
+        &self.rest_shape
+    }
+    pub fn decode(&self, buf: &mut Cursor<Bytes>)
+        -> Result<(T, Value), DecodeError>
+    {
+        ensure!(buf.remaining() >= 4, errors::Underflow);
+        let size = buf.get_u32() as usize;
+        ensure!(size == self.total, errors::ObjectSizeMismatch);
+        let mut chunks = Vec::with_capacity(size);
+        for _ in 0..size {
+            ensure!(buf.remaining() >= 8, errors::Underflow);
+            let _reserved = buf.get_i32();
+            let len = buf.get_i32();
+            if len < 0 {
+                ensure!(len == -1, errors::InvalidMarker);
+                chunks.push(None);
+                continue;
+            }
+            let len = len as usize;
+            ensure!(buf.remaining() >= len, errors::Underflow);
+            let off = buf.position() as usize;
+            chunks.push(Some(buf.get_ref().slice(off..off + len)));
+            buf.advance(len);
+        }
+        ensure!(buf.bytes().len() == 0, errors::ExtraData);
+
+        // Re-pack known fields as an object of their own
+        let mut typed = BytesMut::with_capacity(4 + 8*self.known.len());
+        typed.put_u32(self.known.len() as u32);
+        for &idx in &self.known {
+            typed.reserve(8);
+            typed.put_u32(0);
+            match &chunks[idx] {
+                Some(chunk) => {
+                    typed.put_u32(chunk.len() as u32);
+                    typed.extend_from_slice(chunk);
+                }
+                None => typed.put_i32(-1),
+            }
+        }
+        let typed = T::decode(&mut Cursor::new(typed.freeze()))?;
+
+        let mut fields = Vec::with_capacity(self.rest.len());
+        for (idx, codec) in &self.rest {
+            fields.push(match &chunks[*idx] {
+                Some(chunk) => {
+                    Some(codec.decode_value(&mut Cursor::new(chunk.clone()))?)
+                }
+                None => None,
+            });
+        }
+        Ok((typed, Value::Object {
+            shape: self.rest_shape.clone(),
+            fields,
+        }))
+    }
+}
@@ -0,0 +1,95 @@
+//! Decoding `std::duration` into `std::time::Duration`
+//!
+//! `std::time::Duration` can't be negative, so the policy for negative
+//! values is chosen by type: `std::time::Duration` itself returns an error,
+//! `AbsDuration` drops the sign.
+use std::io::Cursor;
+use std::ops::Deref;
+
+use bytes::{Bytes, Buf};
+use snafu::ensure;
+
+use crate::codec;
+use crate::descriptors::TypePos;
+use crate::errors::{self, DecodeError};
+use crate::queryable::{Queryable, DescriptorContext, DescriptorMismatch};
+use crate::value;
+
+
+/// Absolute value of `std::duration` as `std::time::Duration`
+///
+/// ```rust,ignore
+/// #[derive(Queryable)]
+/// struct Job {
+///     name: String,
+///     elapsed: AbsDuration,
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct AbsDuration(pub std::time::Duration);
+
+impl AbsDuration {
+    pub fn into_inner(self) -> std::time::Duration {
+        self.0
+    }
+}
+
+impl Deref for AbsDuration {
+    type Target = std::time::Duration;
+    fn deref(&self) -> &std::time::Duration {
+        &self.0
+    }
+}
+
+fn decode_duration(buf: &mut Cursor<Bytes>)
+    -> Result<value::Duration, DecodeError>
+{
+    ensure!(buf.remaining() >= 16, errors::Underflow);
+    let micros = buf.get_i64();
+    let days = buf.get_u32();
+    let months = buf.get_u32();
+    ensure!(months == 0 && days == 0, errors::NonZeroReservedBytes);
+    Ok(value::Duration::from_micros(micros))
+}
+
+fn check_duration(ctx: &DescriptorContext, type_pos: TypePos)
+    -> Result<(), DescriptorMismatch>
+{
+    use crate::descriptors::Descriptor::{Scalar, BaseScalar};
+    let desc = ctx.get(type_pos)?;
+    match desc {
+        Scalar(scalar) => {
+            return check_duration(ctx, scalar.base_type_pos);
+        }
+        BaseScalar(base) if base.id == codec::STD_DURATION => {
+            return Ok(());
+        }
+        _ => {}
+    }
+    Err(ctx.wrong_type(desc, "duration"))
+}
+
+/// Negative durations are decoded as `NegativeDuration` error
+impl Queryable for std::time::Duration {
+    fn decode_raw(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
+        let duration = decode_duration(buf)?;
+        ensure!(!duration.is_negative(), errors::NegativeDuration);
+        Ok(duration.abs_duration())
+    }
+    fn check_descriptor(ctx: &DescriptorContext, type_pos: TypePos)
+        -> Result<(), DescriptorMismatch>
+    {
+        check_duration(ctx, type_pos)
+    }
+}
+
+impl Queryable for AbsDuration {
+    fn decode_raw(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
+        Ok(AbsDuration(decode_duration(buf)?.abs_duration()))
+    }
+    fn check_descriptor(ctx: &DescriptorContext, type_pos: TypePos)
+        -> Result<(), DescriptorMismatch>
+    {
+        check_duration(ctx, type_pos)
+    }
+}
@@ -0,0 +1,72 @@
+use std::error::Error;
+use std::io::Cursor;
+use std::ops::{Deref, DerefMut};
+
+use bytes::{Bytes, Buf};
+use snafu::{ensure, ResultExt};
+
+use crate::codec;
+use crate::descriptors::TypePos;
+use crate::errors::{self, DecodeError};
+use crate::queryable::{Queryable, DescriptorContext, DescriptorMismatch};
+
+
+/// Value of `std::json` type deserialized into `T`
+///
+/// ```rust,ignore
+/// #[derive(Queryable)]
+/// struct User {
+///     name: String,
+///     settings: Json<Settings>,
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> Queryable for Json<T> {
+    fn decode_raw(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
+        ensure!(buf.remaining() >= 1, errors::Underflow);
+        let format = buf.get_u8();
+        ensure!(format == 1, errors::InvalidJsonFormat);
+        let value = serde_json::from_slice(buf.bytes())
+            .map_err(|e| -> Box<dyn Error + Send + Sync> { Box::new(e) })
+            .context(errors::InvalidJson)?;
+        buf.advance(buf.remaining());
+        Ok(Json(value))
+    }
+    fn check_descriptor(ctx: &DescriptorContext, type_pos: TypePos)
+        -> Result<(), DescriptorMismatch>
+    {
+        use crate::descriptors::Descriptor::{Scalar, BaseScalar};
+        let desc = ctx.get(type_pos)?;
+        match desc {
+            Scalar(scalar) => {
+                return Self::check_descriptor(ctx, scalar.base_type_pos);
+            }
+            BaseScalar(base) if base.id == codec::STD_JSON => {
+                return Ok(());
+            }
+            _ => {}
+        }
+        Err(ctx.wrong_type(desc, "json"))
+    }
+}
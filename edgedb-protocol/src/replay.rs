@@ -0,0 +1,172 @@
+//! Offline decoding of captured protocol sessions
+//!
+//! Takes raw bytes as they were sent over the wire (one direction of the
+//! connection) and runs them through the same decoding pipeline the client
+//! uses: messages are parsed, data descriptions are turned into codecs and
+//! data rows are decoded into values.
+use std::io::Cursor;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use snafu::{Snafu, ResultExt, ensure};
+
+use crate::client_message::ClientMessage;
+use crate::codec::{Codec, Limits};
+use crate::errors::{self, DecodeError, CodecError};
+use crate::features::ProtocolVersion;
+use crate::server_message::ServerMessage;
+use crate::value::Value;
+
+
+#[derive(Snafu, Debug)]
+#[non_exhaustive]
+pub enum ReplayError {
+    #[snafu(display("error decoding message #{}: {}", index, source))]
+    Message { index: usize, source: DecodeError },
+    #[snafu(display("can't build codec for message #{}: {}", index, source))]
+    BuildCodec { index: usize, source: CodecError },
+    #[snafu(display("error decoding row of message #{}: {}", index, source))]
+    Row { index: usize, source: DecodeError },
+    #[snafu(display("data message #{} received before data description",
+                    index))]
+    NoDescription { index: usize },
+}
+
+/// Iterator over protocol frames in a byte stream
+///
+/// Each item contains the whole frame including message type and length,
+/// i.e. the format that `ServerMessage::decode` and `ClientMessage::decode`
+/// accept.
+#[derive(Debug)]
+pub struct Frames {
+    data: Bytes,
+    pos: usize,
+}
+
+/// A message and values decoded from it
+#[derive(Debug)]
+pub struct Event {
+    pub message: ServerMessage,
+    /// Rows of the `Data` message, empty for other messages
+    pub rows: Vec<Value>,
+}
+
+/// Decoder state for a server to client stream
+#[derive(Debug)]
+pub struct Replay {
+    limits: Limits,
+    proto: ProtocolVersion,
+    codec: Option<Arc<dyn Codec>>,
+    index: usize,
+}
+
+pub fn frames(data: Bytes) -> Frames {
+    Frames { data, pos: 0 }
+}
+
+/// Decodes all client messages in a captured stream
+pub fn client_messages(data: Bytes) -> Result<Vec<ClientMessage>, ReplayError>
+{
+    frames(data).enumerate().map(|(index, frame)| {
+        frame.and_then(|frame| ClientMessage::decode(&frame))
+            .context(Message { index })
+    }).collect()
+}
+
+impl Iterator for Frames {
+    type Item = Result<Bytes, DecodeError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.data.len() {
+            return None;
+        }
+        let result = frame_len(&self.data[self.pos..]).map(|len| {
+            let frame = self.data.slice(self.pos..self.pos + len);
+            self.pos += len;
+            frame
+        });
+        if result.is_err() {
+            // don't try to resync after broken frame
+            self.pos = self.data.len();
+        }
+        Some(result)
+    }
+}
+
+fn frame_len(data: &[u8]) -> Result<usize, DecodeError> {
+    ensure!(data.len() >= 5, errors::Underflow);
+    let len = u32::from_be_bytes([data[1], data[2], data[3], data[4]])
+        as usize;
+    ensure!(len >= 4, errors::Underflow);
+    ensure!(data.len() >= len + 1, errors::Underflow);
+    Ok(len + 1)
+}
+
+impl Default for Replay {
+    fn default() -> Replay {
+        Replay::new()
+    }
+}
+
+impl Replay {
+    pub fn new() -> Replay {
+        Replay::with_limits(Limits::default())
+    }
+    pub fn with_limits(limits: Limits) -> Replay {
+        Replay {
+            limits,
+            proto: ProtocolVersion::current(),
+            codec: None,
+            index: 0,
+        }
+    }
+    /// Sets protocol version used to decode type descriptors
+    ///
+    /// Version is also updated when `ServerHandshake` is received.
+    pub fn set_protocol(&mut self, proto: ProtocolVersion) {
+        self.proto = proto;
+    }
+    /// Decodes a single server message frame
+    pub fn feed(&mut self, frame: &Bytes) -> Result<Event, ReplayError> {
+        let index = self.index;
+        self.index += 1;
+        let message = ServerMessage::decode_with_version(frame, &self.proto)
+            .context(Message { index })?;
+        let mut rows = Vec::new();
+        match &message {
+            ServerMessage::ServerHandshake(hs) => {
+                self.proto = ProtocolVersion::new(hs.major_ver, hs.minor_ver);
+            }
+            ServerMessage::CommandDataDescription(desc) => {
+                let out = desc.output_with_version(&self.proto)
+                    .context(Message { index })?;
+                self.codec = Some(out.build_codec_with_limits(&self.limits)
+                    .context(BuildCodec { index })?);
+            }
+            ServerMessage::CommandDataDescription1(desc) => {
+                let out = desc.output().context(Message { index })?;
+                self.codec = Some(out.build_codec_with_limits(&self.limits)
+                    .context(BuildCodec { index })?);
+            }
+            ServerMessage::Data(data) => {
+                let codec = self.codec.as_ref()
+                    .ok_or(ReplayError::NoDescription { index })?;
+                for chunk in &data.data {
+                    let mut cur = Cursor::new(chunk.clone());
+                    rows.push(codec.decode_value(&mut cur)
+                        .context(Row { index })?);
+                }
+            }
+            _ => {}
+        }
+        Ok(Event { message, rows })
+    }
+    /// Decodes all messages in a captured server to client stream
+    pub fn run(&mut self, data: Bytes) -> Result<Vec<Event>, ReplayError> {
+        let mut events = Vec::new();
+        for frame in frames(data) {
+            let frame = frame.context(Message { index: self.index })?;
+            events.push(self.feed(&frame)?);
+        }
+        Ok(events)
+    }
+}
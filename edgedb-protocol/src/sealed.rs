@@ -0,0 +1,4 @@
+/// Marker for traits that can only be implemented inside this crate
+///
+/// Adding methods to such traits is not a breaking change.
+pub trait Sealed {}
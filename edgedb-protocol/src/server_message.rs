@@ -11,7 +11,13 @@ use snafu::{OptionExt, ensure};
 use crate::errors::{self, EncodeError, DecodeError};
 use crate::encoding::{Headers, Decode, Encode};
 use crate::descriptors::{OutputTypedesc, InputTypedesc, Descriptor, TypePos};
-pub use crate::common::Cardinality;
+use crate::features::ProtocolVersion;
+use crate::value::{Value, Duration};
+pub use crate::common::{Cardinality, ShapeCardinality, Capabilities};
+
+pub const PARAM_SYSTEM_CONFIG: &[u8] = b"system_config";
+pub const PARAM_SUGGESTED_POOL_CONCURRENCY: &[u8] =
+    b"suggested_pool_concurrency";
 
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,6 +34,9 @@ pub enum ServerMessage {
     CommandComplete(CommandComplete),
     PrepareComplete(PrepareComplete),
     CommandDataDescription(CommandDataDescription),
+    /// Data description of protocol 1.0, which uses the same message
+    /// type as `CommandDataDescription`
+    CommandDataDescription1(CommandDataDescription1),
     Data(Data),
     RestoreReady(RestoreReady),
     // Don't decode Dump packets here as we only need to process them as
@@ -114,6 +123,13 @@ pub struct ParameterStatus {
     pub value: Bytes,
 }
 
+/// Typed subset of server settings sent in `system_config` parameter
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SystemConfig {
+    pub session_idle_timeout: Option<Duration>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CommandComplete {
     pub headers: Headers,
@@ -138,6 +154,18 @@ pub struct CommandDataDescription {
     pub output_typedesc: Bytes,
 }
 
+/// Data description message of protocol 1.0 and later
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandDataDescription1 {
+    pub headers: Headers,
+    pub capabilities: Capabilities,
+    pub result_cardinality: ShapeCardinality,
+    pub input_typedesc_id: Uuid,
+    pub input_typedesc: Bytes,
+    pub output_typedesc_id: Uuid,
+    pub output_typedesc: Bytes,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Data {
     pub data: Vec<Bytes>,
@@ -170,44 +198,141 @@ fn encode<T: Encode>(buf: &mut BytesMut, code: u8, msg: &T)
     Ok(())
 }
 
-impl CommandDataDescription {
-    pub fn output(&self) -> Result<OutputTypedesc, DecodeError> {
-        let mut cur = Cursor::new(self.output_typedesc.clone());
-        let mut descriptors = Vec::new();
-        while cur.bytes() != b"" {
-            match Descriptor::decode(&mut cur)? {
-                Descriptor::TypeAnnotation(_) => {}
-                item => descriptors.push(item),
-            }
+fn decode_descriptors(data: &Bytes, proto: &ProtocolVersion)
+    -> Result<Vec<Descriptor>, DecodeError>
+{
+    let mut cur = Cursor::new(data.clone());
+    let mut descriptors = Vec::new();
+    while cur.bytes() != b"" {
+        match Descriptor::decode_with_version(&mut cur, proto)? {
+            Descriptor::TypeAnnotation(_) => {}
+            item => descriptors.push(item),
         }
-        let root_id = self.output_typedesc_id.clone();
-        let root_pos = if root_id == Uuid::from_u128(0) {
-            None
-        } else {
-            let idx = descriptors.iter().position(|x| *x.id() == root_id)
-                .context(errors::UuidNotFound { uuid: root_id })?;
-            let pos = idx.try_into().ok()
-                .context(errors::TooManyDescriptors { index: idx })?;
-            Some(TypePos(pos))
+    }
+    Ok(descriptors)
+}
+
+fn root_pos(descriptors: &[Descriptor], root_id: &Uuid)
+    -> Result<TypePos, DecodeError>
+{
+    let idx = descriptors.iter().position(|x| x.id() == root_id)
+        .context(errors::UuidNotFound { uuid: root_id.clone() })?;
+    let pos = idx.try_into().ok()
+        .context(errors::TooManyDescriptors { index: idx })?;
+    Ok(TypePos(pos))
+}
+
+fn output_typedesc(root_id: &Uuid, data: &Bytes, proto: &ProtocolVersion)
+    -> Result<OutputTypedesc, DecodeError>
+{
+    let descriptors = decode_descriptors(data, proto)?;
+    let root_pos = if *root_id == Uuid::from_u128(0) {
+        None
+    } else {
+        Some(root_pos(&descriptors, root_id)?)
+    };
+    Ok(OutputTypedesc {
+        array: descriptors,
+        root_id: root_id.clone(),
+        root_pos,
+    })
+}
+
+fn input_typedesc(root_id: &Uuid, data: &Bytes, proto: &ProtocolVersion)
+    -> Result<InputTypedesc, DecodeError>
+{
+    let descriptors = decode_descriptors(data, proto)?;
+    let root_pos = root_pos(&descriptors, root_id)?;
+    Ok(InputTypedesc {
+        array: descriptors,
+        root_id: root_id.clone(),
+        root_pos,
+    })
+}
+
+impl ParameterStatus {
+    /// Returns suggested connection pool size if this is a
+    /// `suggested_pool_concurrency` parameter
+    pub fn suggested_pool_concurrency(&self) -> Option<usize> {
+        if &self.name[..] != PARAM_SUGGESTED_POOL_CONCURRENCY {
+            return None;
+        }
+        std::str::from_utf8(&self.value).ok()?.parse().ok()
+    }
+    /// Decodes type descriptor and data of the `system_config` parameter
+    ///
+    /// Data is an object which can be decoded by a codec built from the
+    /// returned descriptor, and then converted to `SystemConfig`. Returns
+    /// `None` if this message contains some other parameter.
+    pub fn parse_system_config(&self)
+        -> Option<Result<(OutputTypedesc, Bytes), DecodeError>>
+    {
+        self.parse_system_config_with_version(&ProtocolVersion::current())
+    }
+    pub fn parse_system_config_with_version(&self, proto: &ProtocolVersion)
+        -> Option<Result<(OutputTypedesc, Bytes), DecodeError>>
+    {
+        if &self.name[..] != PARAM_SYSTEM_CONFIG {
+            return None;
+        }
+        let parse = || -> Result<_, DecodeError> {
+            let mut cur = Cursor::new(self.value.clone());
+            let typedesc = Bytes::decode(&mut cur)?;
+            let data = Bytes::decode(&mut cur)?;
+            ensure!(cur.bytes().is_empty(), errors::ExtraData);
+            let mut desc_cur = Cursor::new(typedesc);
+            let root_id = Uuid::decode(&mut desc_cur)?;
+            let pos = desc_cur.position() as usize;
+            let descriptors = desc_cur.get_ref().slice(pos..);
+            Ok((output_typedesc(&root_id, &descriptors, proto)?, data))
         };
-        Ok(OutputTypedesc { array: descriptors, root_id, root_pos })
+        Some(parse())
+    }
+}
+
+impl SystemConfig {
+    /// Extracts known settings from the decoded `system_config` object
+    ///
+    /// Unknown fields are ignored, settings that are absent or have
+    /// unexpected type are left as `None`.
+    pub fn from_value(value: &Value) -> SystemConfig {
+        let session_idle_timeout = match value.field("session_idle_timeout") {
+            Some(Value::Duration(d)) => Some(*d),
+            _ => None,
+        };
+        SystemConfig { session_idle_timeout }
+    }
+}
+
+impl CommandDataDescription {
+    pub fn output(&self) -> Result<OutputTypedesc, DecodeError> {
+        self.output_with_version(&ProtocolVersion::current())
+    }
+    /// Decodes output descriptors sent using specified protocol version
+    pub fn output_with_version(&self, proto: &ProtocolVersion)
+        -> Result<OutputTypedesc, DecodeError>
+    {
+        output_typedesc(&self.output_typedesc_id, &self.output_typedesc, proto)
     }
     pub fn input(&self) -> Result<InputTypedesc, DecodeError> {
-        let mut cur = Cursor::new(self.input_typedesc.clone());
-        let mut descriptors = Vec::new();
-        while cur.bytes() != b"" {
-            match Descriptor::decode(&mut cur)? {
-                Descriptor::TypeAnnotation(_) => {}
-                item => descriptors.push(item),
-            }
-        }
-        let root_id = self.input_typedesc_id.clone();
-        let idx = descriptors.iter().position(|x| *x.id() == root_id)
-            .context(errors::UuidNotFound { uuid: root_id })?;
-        let pos = idx.try_into().ok()
-            .context(errors::TooManyDescriptors { index: idx })?;
-        let root_pos = TypePos(pos);
-        Ok(InputTypedesc { array: descriptors, root_id, root_pos })
+        self.input_with_version(&ProtocolVersion::current())
+    }
+    /// Decodes input descriptors sent using specified protocol version
+    pub fn input_with_version(&self, proto: &ProtocolVersion)
+        -> Result<InputTypedesc, DecodeError>
+    {
+        input_typedesc(&self.input_typedesc_id, &self.input_typedesc, proto)
+    }
+}
+
+impl CommandDataDescription1 {
+    pub fn output(&self) -> Result<OutputTypedesc, DecodeError> {
+        output_typedesc(&self.output_typedesc_id, &self.output_typedesc,
+                        &ProtocolVersion::new(1, 0))
+    }
+    pub fn input(&self) -> Result<InputTypedesc, DecodeError> {
+        input_typedesc(&self.input_typedesc_id, &self.input_typedesc,
+                       &ProtocolVersion::new(1, 0))
     }
 }
 
@@ -225,6 +350,7 @@ impl ServerMessage {
             CommandComplete(h) => encode(buf, 0x43, h),
             PrepareComplete(h) => encode(buf, 0x31, h),
             CommandDataDescription(h) => encode(buf, 0x54, h),
+            CommandDataDescription1(h) => encode(buf, 0x54, h),
             Data(h) => encode(buf, 0x44, h),
             RestoreReady(h) => encode(buf, 0x2b, h),
             DumpHeader(h) => encode(buf, 0x40, h),
@@ -241,6 +367,15 @@ impl ServerMessage {
     /// arbitrary error or be silent if message is only partially present
     /// in the buffer or if extra data present.
     pub fn decode(buf: &Bytes) -> Result<ServerMessage, DecodeError> {
+        ServerMessage::decode_with_version(buf, &ProtocolVersion::current())
+    }
+    /// Decode one frame sent using specified protocol version
+    ///
+    /// Same as `decode` except for messages that changed their format in
+    /// protocol 1.0.
+    pub fn decode_with_version(buf: &Bytes, proto: &ProtocolVersion)
+        -> Result<ServerMessage, DecodeError>
+    {
         use self::ServerMessage as M;
         let mut data = Cursor::new(buf.slice(5..));
         match buf[0] {
@@ -257,6 +392,10 @@ impl ServerMessage {
             0x2b => RestoreReady::decode(&mut data).map(M::RestoreReady),
             0x40 => RawPacket::decode(&mut data).map(M::DumpHeader),
             0x3d => RawPacket::decode(&mut data).map(M::DumpBlock),
+            0x54 if proto.is_at_least(1, 0) => {
+                CommandDataDescription1::decode(&mut data)
+                .map(M::CommandDataDescription1)
+            }
             0x54 => {
                 CommandDataDescription::decode(&mut data)
                 .map(M::CommandDataDescription)
@@ -700,6 +839,66 @@ impl Decode for CommandDataDescription {
     }
 }
 
+impl Encode for CommandDataDescription1 {
+    fn encode(&self, buf: &mut BytesMut)
+        -> Result<(), EncodeError>
+    {
+        buf.reserve(51);
+        buf.put_u16(u16::try_from(self.headers.len()).ok()
+            .context(errors::TooManyHeaders)?);
+        for (&name, value) in &self.headers {
+            buf.reserve(2);
+            buf.put_u16(name);
+            value.encode(buf)?;
+        }
+        buf.reserve(49);
+        buf.put_u64(self.capabilities.bits());
+        buf.put_u8(self.result_cardinality as u8);
+        self.input_typedesc_id.encode(buf)?;
+        self.input_typedesc.encode(buf)?;
+        self.output_typedesc_id.encode(buf)?;
+        self.output_typedesc.encode(buf)?;
+        Ok(())
+    }
+}
+
+impl Decode for CommandDataDescription1 {
+    fn decode(buf: &mut Cursor<Bytes>) -> Result<Self, DecodeError> {
+        ensure!(buf.remaining() >= 51, errors::Underflow);
+        let num_headers = buf.get_u16();
+        let mut headers = HashMap::new();
+        for _ in 0..num_headers {
+            ensure!(buf.remaining() >= 4, errors::Underflow);
+            headers.insert(buf.get_u16(), Bytes::decode(buf)?);
+        }
+        ensure!(buf.remaining() >= 49, errors::Underflow);
+        let capabilities = Capabilities::from_bits_truncate(buf.get_u64());
+        let result_cardinality = match buf.get_u8() {
+            0x6e => ShapeCardinality::NoResult,
+            0x6f => ShapeCardinality::AtMostOne,
+            0x41 => ShapeCardinality::One,
+            0x6d => ShapeCardinality::Many,
+            0x4d => ShapeCardinality::AtLeastOne,
+            c => errors::InvalidCardinality { cardinality: c }.fail()?,
+        };
+
+        let input_typedesc_id = Uuid::decode(buf)?;
+        let input_typedesc = Bytes::decode(buf)?;
+        let output_typedesc_id = Uuid::decode(buf)?;
+        let output_typedesc = Bytes::decode(buf)?;
+
+        Ok(CommandDataDescription1 {
+            headers,
+            capabilities,
+            result_cardinality,
+            input_typedesc_id,
+            input_typedesc,
+            output_typedesc_id,
+            output_typedesc,
+        })
+    }
+}
+
 impl Encode for Data {
     fn encode(&self, buf: &mut BytesMut)
         -> Result<(), EncodeError>
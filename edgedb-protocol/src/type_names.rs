@@ -0,0 +1,185 @@
+//! Human-readable names of types in type descriptors
+use uuid::Uuid;
+
+use crate::codec::{STD_UUID, STD_STR, STD_BYTES, STD_INT16, STD_INT32};
+use crate::codec::{STD_INT64, STD_FLOAT32, STD_FLOAT64, STD_DECIMAL};
+use crate::codec::{STD_BOOL, STD_DATETIME, CAL_LOCAL_DATETIME};
+use crate::codec::{CAL_LOCAL_DATE, CAL_LOCAL_TIME, STD_DURATION, STD_JSON};
+use crate::codec::{STD_BIGINT};
+use crate::descriptors::{Descriptor, TypePos};
+
+
+pub(crate) struct ScalarType {
+    /// EdgeQL name of the type
+    pub name: &'static str,
+    /// `Value::kind()` of the values of this type
+    pub value_kind: &'static str,
+    /// Rust type implementing `Queryable` for the scalar, if any
+    pub rust_type: Option<&'static str>,
+}
+
+/// Nesting of descriptors followed when formatting names
+///
+/// Descriptors received from the server are never that deep, but nothing
+/// prevents a malformed set of descriptors from referring to itself.
+const MAX_DEPTH: usize = 32;
+
+pub(crate) fn scalar_type(id: &Uuid) -> Option<ScalarType> {
+    let (name, value_kind, rust_type) = match *id {
+        STD_UUID => ("std::uuid", "uuid", Some("Uuid")),
+        STD_STR => ("std::str", "string", Some("String")),
+        STD_BYTES => ("std::bytes", "bytes", None),
+        STD_INT16 => ("std::int16", "int16", None),
+        STD_INT32 => ("std::int32", "int32", None),
+        STD_INT64 => ("std::int64", "int64", Some("i64")),
+        STD_FLOAT32 => ("std::float32", "float32", None),
+        STD_FLOAT64 => ("std::float64", "float64", None),
+        STD_DECIMAL => ("std::decimal", "decimal", None),
+        STD_BOOL => ("std::bool", "bool", Some("bool")),
+        STD_DATETIME => ("std::datetime", "datetime", None),
+        CAL_LOCAL_DATETIME => ("cal::local_datetime", "cal::local_datetime",
+                               None),
+        CAL_LOCAL_DATE => ("cal::local_date", "cal::local_date", None),
+        CAL_LOCAL_TIME => ("cal::local_time", "cal::local_time", None),
+        STD_DURATION => ("std::duration", "duration",
+                         Some("std::time::Duration")),
+        STD_JSON => ("std::json", "json", json_type()),
+        STD_BIGINT => ("std::bigint", "bigint", None),
+        _ => return None,
+    };
+    Some(ScalarType { name, value_kind, rust_type })
+}
+
+#[cfg(feature="with-serde")]
+fn json_type() -> Option<&'static str> {
+    Some("Json<T>")
+}
+
+#[cfg(not(feature="with-serde"))]
+fn json_type() -> Option<&'static str> {
+    None
+}
+
+/// Formats type at `pos` in EdgeQL-like syntax, e.g. `array<std::str>`
+pub(crate) fn type_name(descriptors: &[Descriptor], pos: TypePos) -> String {
+    name_at(descriptors, pos, 0)
+}
+
+pub(crate) fn descriptor_name(descriptors: &[Descriptor], desc: &Descriptor)
+    -> String
+{
+    name_of(descriptors, desc, 0)
+}
+
+fn name_at(descriptors: &[Descriptor], pos: TypePos, depth: usize)
+    -> String
+{
+    match descriptors.get(pos.0 as usize) {
+        Some(desc) => name_of(descriptors, desc, depth),
+        None => format!("<invalid type position {}>", pos.0),
+    }
+}
+
+fn name_of(descriptors: &[Descriptor], desc: &Descriptor, depth: usize)
+    -> String
+{
+    use Descriptor::*;
+    if depth >= MAX_DEPTH {
+        return "...".into();
+    }
+    let name = |pos| name_at(descriptors, pos, depth+1);
+    match desc {
+        BaseScalar(d) => match scalar_type(&d.id) {
+            Some(scalar) => scalar.name.into(),
+            None => format!("<scalar {}>", d.id),
+        },
+        Scalar(d) => name(d.base_type_pos),
+        Set(d) => format!("set<{}>", name(d.type_pos)),
+        Array(d) => format!("array<{}>", name(d.type_pos)),
+        Tuple(d) => {
+            let items = d.element_types.iter()
+                .map(|&pos| name(pos))
+                .collect::<Vec<_>>();
+            format!("tuple<{}>", items.join(", "))
+        }
+        NamedTuple(d) => {
+            let items = d.elements.iter()
+                .map(|el| format!("{}: {}", el.name, name(el.type_pos)))
+                .collect::<Vec<_>>();
+            format!("tuple<{}>", items.join(", "))
+        }
+        Enumeration(d) => format!("enum<{}>", d.members.join(", ")),
+        ObjectShape(_) => "object".into(),
+        TypeAnnotation(d) => d.annotation.clone(),
+    }
+}
+
+/// Suggests Rust type that can be decoded from the descriptor
+///
+/// Only types implementing `Queryable` are suggested. Returns `None` if
+/// there is no such type, e.g. for arrays, tuples and most scalars.
+pub(crate) fn rust_type(descriptors: &[Descriptor], desc: &Descriptor)
+    -> Option<String>
+{
+    rust_type_of(descriptors, desc, 0)
+}
+
+fn rust_type_of(descriptors: &[Descriptor], desc: &Descriptor, depth: usize)
+    -> Option<String>
+{
+    use Descriptor::*;
+    if depth >= MAX_DEPTH {
+        return None;
+    }
+    let item = |pos: TypePos| {
+        descriptors.get(pos.0 as usize)
+            .and_then(|d| rust_type_of(descriptors, d, depth+1))
+    };
+    let result = match desc {
+        BaseScalar(d) => scalar_type(&d.id)?.rust_type?.into(),
+        Scalar(d) => item(d.base_type_pos)?,
+        Set(d) => format!("BTreeSet<{}>", item(d.type_pos)?),
+        ObjectShape(_) => "a struct with #[derive(Queryable)]".into(),
+        Array(_) | Tuple(_) | NamedTuple(_) | Enumeration(_)
+        | TypeAnnotation(_) => return None,
+    };
+    Some(result)
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::codec::STD_INT16;
+    use crate::descriptors::{Descriptor, TypePos, SetDescriptor};
+    use crate::descriptors::{BaseScalarTypeDescriptor, ScalarTypeDescriptor};
+    use super::{type_name, rust_type};
+
+    #[test]
+    fn suggestions() {
+        let descriptors = vec![
+            Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+                id: STD_INT16,
+            }),
+            Descriptor::Set(SetDescriptor {
+                id: Uuid::from_u128(0x1000),
+                type_pos: TypePos(0),
+            }),
+        ];
+        // there is no `Queryable` for `i16`
+        assert_eq!(rust_type(&descriptors, &descriptors[1]), None);
+    }
+
+    #[test]
+    fn max_depth() {
+        // scalar referring to itself
+        let descriptors = vec![
+            Descriptor::Scalar(ScalarTypeDescriptor {
+                id: Uuid::from_u128(0x1000),
+                base_type_pos: TypePos(0),
+            }),
+        ];
+        assert_eq!(type_name(&descriptors, TypePos(0)), "...");
+        assert_eq!(rust_type(&descriptors, &descriptors[0]), None);
+    }
+}
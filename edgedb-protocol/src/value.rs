@@ -1,16 +1,59 @@
+use std::convert::TryFrom;
 use std::fmt;
-use std::time::{SystemTime};
+use std::ops::{Add, Sub, Neg};
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{u32, u64, i32};
 
 use uuid::Uuid;
 
 use crate::codec::{NamedTupleShape, ObjectShape, EnumValue};
 
-#[derive(Clone, Debug, PartialEq)]
+#[cfg(feature="arbitrary")]
+mod arbitrary_impls;
+mod builder;
+mod clock;
+mod hashable;
+mod parse;
+mod path;
+
+pub use builder::{ObjectBuilder, NamedTupleBuilder};
+pub use clock::{Clock, SystemClock, TestClock};
+pub use hashable::HashableValue;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Duration {
     pub(crate) micros: i64,
 }
 
+/// A point in time (timezone aware) with microsecond precision
+///
+/// Internally stored as microseconds since 2000-01-01T00:00:00Z, the same
+/// way it's sent on the wire.
+///
+/// Conversions from types with higher precision (`SystemTime`, chrono and
+/// time crate types) silently drop sub-microsecond part. Use
+/// `DatetimeNanos` when that needs to be detected or rounded explicitly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Datetime {
+    pub(crate) micros: i64,
+}
+
+/// A point in time with nanosecond precision
+///
+/// The database stores only microseconds, so this type is meant for
+/// client-side use: it keeps nanoseconds of the original value, and
+/// converting it to `Datetime` requires choosing what to do with them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DatetimeNanos {
+    datetime: Datetime,
+    // always less than 1000
+    nanos: u16,
+}
+
+/// Error returned when a value can't be converted without losing precision
+#[derive(Debug)]
+pub struct PrecisionLoss;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
     Nothing,
@@ -25,12 +68,12 @@ pub enum Value {
     BigInt(BigInt),
     Decimal(Decimal),
     Bool(bool),
-    Datetime(SystemTime),
+    Datetime(Datetime),
     LocalDatetime(LocalDatetime),
     LocalDate(LocalDate),
     LocalTime(LocalTime),
     Duration(Duration),
-    Json(String),  // or should we use serde::Json?
+    Json(Json),
     Set(Vec<Value>),
     Object { shape: ObjectShape, fields: Vec<Option<Value>> },
     Tuple(Vec<Value>),
@@ -39,14 +82,14 @@ pub enum Value {
     Enum(EnumValue),
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct BigInt {
     pub(crate) negative: bool,
     pub(crate) weight: i16,
     pub(crate) digits: Vec<u16>,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Decimal {
     pub(crate) negative: bool,
     pub(crate) weight: i16,
@@ -54,21 +97,28 @@ pub struct Decimal {
     pub(crate) digits: Vec<u16>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LocalDatetime {
     pub(crate) micros: i64,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct LocalDate {
     pub(crate) days: i32,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct LocalTime {
     pub(crate) micros: i64,
 }
 
+/// JSON text as received from (or sent to) the database
+///
+/// Values decoded from the database are not validated, the server always
+/// sends valid JSON.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Json(String);
+
 #[derive(Debug)]
 pub struct OutOfRange;
 
@@ -79,12 +129,125 @@ impl fmt::Display for OutOfRange {
     }
 }
 
+impl std::error::Error for PrecisionLoss {}
+impl fmt::Display for PrecisionLoss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        "value can't be represented without losing precision".fmt(f)
+    }
+}
+
+impl Json {
+    /// Wraps a string without checking that it contains valid JSON
+    ///
+    /// The server rejects invalid JSON when such value is used as a query
+    /// argument.
+    pub fn new_unchecked(value: String) -> Json {
+        Json(value)
+    }
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    pub fn into_string(self) -> String {
+        self.0
+    }
+    #[cfg(feature="with-serde")]
+    pub fn new(value: String) -> Result<Json, serde_json::Error> {
+        serde_json::from_str::<serde::de::IgnoredAny>(&value)?;
+        Ok(Json(value))
+    }
+    /// Deserializes JSON text into a value of type `T`
+    #[cfg(feature="with-serde")]
+    pub fn parse<T: serde::de::DeserializeOwned>(&self)
+        -> Result<T, serde_json::Error>
+    {
+        serde_json::from_str(&self.0)
+    }
+}
+
+impl std::ops::Deref for Json {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Json {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<Json> for String {
+    fn from(json: Json) -> String {
+        json.0
+    }
+}
+
+#[cfg(feature="serde_json")]
+impl From<serde_json::Value> for Json {
+    fn from(value: serde_json::Value) -> Json {
+        Json(value.to_string())
+    }
+}
+
+#[cfg(feature="serde_json")]
+impl From<&serde_json::Value> for Json {
+    fn from(value: &serde_json::Value) -> Json {
+        Json(value.to_string())
+    }
+}
+
 impl From<std::num::TryFromIntError> for OutOfRange {
     fn from(_: std::num::TryFromIntError) -> OutOfRange {
         OutOfRange
     }
 }
 
+/// Error converting `Decimal` into `rust_decimal::Decimal`
+#[cfg(feature="rust_decimal")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RustDecimalError {
+    /// Value doesn't fit 96-bit mantissa
+    TooManyDigits,
+    /// Value has more than 28 significant digits after decimal point
+    ScaleTooLarge { scale: u16 },
+}
+
+#[cfg(feature="rust_decimal")]
+impl std::error::Error for RustDecimalError {}
+#[cfg(feature="rust_decimal")]
+impl fmt::Display for RustDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RustDecimalError::TooManyDigits => {
+                "decimal has too many digits for rust_decimal".fmt(f)
+            }
+            RustDecimalError::ScaleTooLarge { scale } => {
+                write!(f, "decimal scale {} is larger than maximum of 28",
+                       scale)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    kind: &'static str,
+}
+
+impl std::error::Error for ParseError {}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid {} value", self.kind)
+    }
+}
+
 
 impl Value {
     pub fn kind(&self) -> &'static str {
@@ -119,9 +282,89 @@ impl Value {
     pub fn empty_tuple() -> Value {
         Value::Tuple(Vec::new())
     }
+    /// Approximate number of bytes of memory occupied by the value
+    ///
+    /// Includes the value itself and all heap allocations it owns. Object
+    /// and named tuple shapes and enum names are shared between all values
+    /// decoded by the same codec, so they aren't counted.
+    pub fn approximate_size(&self) -> usize {
+        use std::mem::size_of;
+        use Value::*;
+        let heap = match self {
+            Nothing | Uuid(..) | Int16(..) | Int32(..) | Int64(..)
+            | Float32(..) | Float64(..) | Bool(..) | Datetime(..)
+            | LocalDatetime(..) | LocalDate(..) | LocalTime(..)
+            | Duration(..) | Enum(..)
+            => 0,
+            Str(s) => s.capacity(),
+            Bytes(b) => b.capacity(),
+            BigInt(b) => b.digits.capacity() * size_of::<u16>(),
+            Decimal(d) => d.digits.capacity() * size_of::<u16>(),
+            Json(j) => j.0.capacity(),
+            Set(items) | Tuple(items) | Array(items)
+            | NamedTuple { fields: items, .. }
+            => vec_size(items),
+            Object { fields, .. } => {
+                fields.capacity() * size_of::<Option<Value>>()
+                + fields.iter().flatten()
+                    .map(|v| v.approximate_size() - size_of::<Value>())
+                    .sum::<usize>()
+            }
+        };
+        size_of::<Value>() + heap
+    }
+}
+
+fn vec_size(items: &Vec<Value>) -> usize {
+    // elements are stored inline, so only their heap part is added
+    items.capacity() * std::mem::size_of::<Value>()
+    + items.iter()
+        .map(|v| v.approximate_size() - std::mem::size_of::<Value>())
+        .sum::<usize>()
+}
+
+macro_rules! value_from {
+    ($($typ:ty => $variant:ident,)*) => {
+        $(
+            impl From<$typ> for Value {
+                fn from(value: $typ) -> Value {
+                    Value::$variant(value)
+                }
+            }
+        )*
+    }
+}
+
+value_from! {
+    Uuid => Uuid,
+    String => Str,
+    i16 => Int16,
+    i32 => Int32,
+    i64 => Int64,
+    f32 => Float32,
+    f64 => Float64,
+    BigInt => BigInt,
+    Decimal => Decimal,
+    bool => Bool,
+    Datetime => Datetime,
+    LocalDatetime => LocalDatetime,
+    LocalDate => LocalDate,
+    LocalTime => LocalTime,
+    Duration => Duration,
+    Json => Json,
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Value {
+        Value::Str(value.into())
+    }
 }
 
 impl Duration {
+    pub const ZERO: Duration = Duration { micros: 0 };
+    pub const MIN: Duration = Duration { micros: i64::min_value() };
+    pub const MAX: Duration = Duration { micros: i64::max_value() };
+
     pub fn from_micros(micros: i64) -> Duration {
         Duration { micros }
     }
@@ -146,6 +389,185 @@ impl Duration {
             return std::time::Duration::from_micros(self.micros as u64);
         }
     }
+    /// Returns `None` on overflow
+    pub fn checked_add(self, other: Duration) -> Option<Duration> {
+        self.micros.checked_add(other.micros).map(Duration::from_micros)
+    }
+    /// Returns `None` on overflow
+    pub fn checked_sub(self, other: Duration) -> Option<Duration> {
+        self.micros.checked_sub(other.micros).map(Duration::from_micros)
+    }
+    /// Returns `None` if duration is the minimum value (its negation
+    /// can't be represented)
+    pub fn checked_neg(self) -> Option<Duration> {
+        self.micros.checked_neg().map(Duration::from_micros)
+    }
+    /// Clamps result to `Duration::MIN..=Duration::MAX` instead of overflowing
+    pub fn saturating_add(self, other: Duration) -> Duration {
+        Duration::from_micros(self.micros.saturating_add(other.micros))
+    }
+    /// Clamps result to `Duration::MIN..=Duration::MAX` instead of overflowing
+    pub fn saturating_sub(self, other: Duration) -> Duration {
+        Duration::from_micros(self.micros.saturating_sub(other.micros))
+    }
+    /// Returns stdlib's duration, negative durations are clamped to zero
+    ///
+    /// Use `TryFrom` to get an error on negative durations instead, or
+    /// `abs_duration` to drop the sign.
+    pub fn to_std_saturating(&self) -> std::time::Duration {
+        if self.micros.is_negative() {
+            std::time::Duration::from_micros(0)
+        } else {
+            std::time::Duration::from_micros(self.micros as u64)
+        }
+    }
+}
+
+impl TryFrom<Duration> for std::time::Duration {
+    type Error = OutOfRange;
+    fn try_from(d: Duration) -> Result<std::time::Duration, Self::Error> {
+        if d.micros.is_negative() {
+            return Err(OutOfRange);
+        }
+        Ok(std::time::Duration::from_micros(d.micros as u64))
+    }
+}
+
+impl TryFrom<std::time::Duration> for Duration {
+    type Error = OutOfRange;
+    fn try_from(d: std::time::Duration) -> Result<Duration, Self::Error> {
+        Ok(Duration { micros: i64::try_from(d.as_micros())? })
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+    fn add(self, other: Duration) -> Duration {
+        self.checked_add(other).expect("overflow when adding durations")
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+    fn sub(self, other: Duration) -> Duration {
+        self.checked_sub(other).expect("overflow when subtracting durations")
+    }
+}
+
+impl Neg for Duration {
+    type Output = Duration;
+    fn neg(self) -> Duration {
+        self.checked_neg().expect("overflow when negating duration")
+    }
+}
+
+impl Datetime {
+    /// Returns `None` if result is out of range
+    pub fn checked_add(self, duration: Duration) -> Option<Datetime> {
+        self.micros.checked_add(duration.micros)
+            .and_then(|micros| Datetime::try_from_micros(micros).ok())
+    }
+    /// Returns `None` if result is out of range
+    pub fn checked_sub(self, duration: Duration) -> Option<Datetime> {
+        self.micros.checked_sub(duration.micros)
+            .and_then(|micros| Datetime::try_from_micros(micros).ok())
+    }
+    /// Returns duration elapsed since `earlier`, negative if `earlier` is
+    /// actually later than `self`, `None` on overflow
+    pub fn checked_duration_since(self, earlier: Datetime)
+        -> Option<Duration>
+    {
+        self.micros.checked_sub(earlier.micros).map(Duration::from_micros)
+    }
+}
+
+impl Add<Duration> for Datetime {
+    type Output = Datetime;
+    fn add(self, duration: Duration) -> Datetime {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to datetime")
+    }
+}
+
+impl Sub<Duration> for Datetime {
+    type Output = Datetime;
+    fn sub(self, duration: Duration) -> Datetime {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from datetime")
+    }
+}
+
+impl Sub for Datetime {
+    type Output = Duration;
+    fn sub(self, other: Datetime) -> Duration {
+        self.checked_duration_since(other)
+            .expect("overflow when subtracting datetimes")
+    }
+}
+
+impl LocalDatetime {
+    /// Returns `None` if result is out of range
+    pub fn checked_add(self, duration: Duration) -> Option<LocalDatetime> {
+        self.micros.checked_add(duration.micros)
+            .and_then(|micros| LocalDatetime::try_from_micros(micros).ok())
+    }
+    /// Returns `None` if result is out of range
+    pub fn checked_sub(self, duration: Duration) -> Option<LocalDatetime> {
+        self.micros.checked_sub(duration.micros)
+            .and_then(|micros| LocalDatetime::try_from_micros(micros).ok())
+    }
+}
+
+impl Add<Duration> for LocalDatetime {
+    type Output = LocalDatetime;
+    fn add(self, duration: Duration) -> LocalDatetime {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to local datetime")
+    }
+}
+
+impl Sub<Duration> for LocalDatetime {
+    type Output = LocalDatetime;
+    fn sub(self, duration: Duration) -> LocalDatetime {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from local datetime")
+    }
+}
+
+impl Sub for LocalDatetime {
+    type Output = Duration;
+    fn sub(self, other: LocalDatetime) -> Duration {
+        self.micros.checked_sub(other.micros).map(Duration::from_micros)
+            .expect("overflow when subtracting local datetimes")
+    }
+}
+
+fn postgres_epoch() -> SystemTime {
+    UNIX_EPOCH + std::time::Duration::from_secs(946684800)
+}
+
+impl TryFrom<SystemTime> for Datetime {
+    type Error = OutOfRange;
+    fn try_from(time: SystemTime) -> Result<Datetime, Self::Error> {
+        let micros = match time.duration_since(postgres_epoch()) {
+            Ok(after) => i64::try_from(after.as_micros())?,
+            Err(e) => -i64::try_from(e.duration().as_micros())?,
+        };
+        Datetime::try_from_micros(micros)
+    }
+}
+
+impl From<Datetime> for SystemTime {
+    fn from(dt: Datetime) -> SystemTime {
+        use std::time::Duration;
+
+        if dt.micros < 0 {
+            postgres_epoch() - Duration::from_micros(
+                u64::MAX - dt.micros as u64 + 1)
+        } else {
+            postgres_epoch() + Duration::from_micros(dt.micros as u64)
+        }
+    }
 }
 
 impl BigInt {
@@ -157,68 +579,191 @@ impl BigInt {
             self.digits.remove(0);
             self.weight -= 1;
         }
+        if self.digits.is_empty() {
+            self.negative = false;
+            self.weight = 0;
+        }
         return self
     }
+    fn from_abs(negative: bool, mut abs: u128) -> BigInt {
+        let mut digits = Vec::new();
+        while abs > 0 {
+            digits.push((abs % 10000) as u16);
+            abs /= 10000;
+        }
+        digits.reverse();
+        BigInt {
+            negative,
+            // u128 has at most 10 base-10000 digits
+            weight: digits.len() as i16 - 1,
+            digits,
+        }.normalize()
+    }
+}
+
+impl From<u128> for BigInt {
+    fn from(v: u128) -> BigInt {
+        BigInt::from_abs(false, v)
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(v: i128) -> BigInt {
+        if v < 0 {
+            BigInt::from_abs(true, (v as u128).wrapping_neg())
+        } else {
+            BigInt::from_abs(false, v as u128)
+        }
+    }
 }
 
 impl From<u64> for BigInt {
     fn from(v: u64) -> BigInt {
-        return BigInt {
-            negative: false,
-            weight: 3,
-            digits: vec![
-                (v / 10000_0000_0000 % 10000) as u16,
-                (v / 10000_0000 % 10000) as u16,
-                (v / 10000 % 10000) as u16,
-                (v % 10000) as u16,
-            ],
-        }.normalize();
+        BigInt::from(v as u128)
     }
 }
 
 impl From<i64> for BigInt {
     fn from(v: i64) -> BigInt {
-        let (abs, negative) = if v < 0 {
-            (u64::MAX - v as u64 + 1, true)
-        } else {
-            (v as u64, false)
-        };
-        return BigInt {
-            negative,
-            weight: 3,
-            digits: vec![
-                (abs / 10000_0000_0000 % 10000) as u16,
-                (abs / 10000_0000 % 10000) as u16,
-                (abs / 10000 % 10000) as u16,
-                (abs % 10000) as u16,
-            ],
-        }.normalize();
+        BigInt::from(v as i128)
     }
 }
 
 impl From<u32> for BigInt {
     fn from(v: u32) -> BigInt {
-        return BigInt {
-            negative: false,
-            weight: 1,
-            digits: vec![(v / 10000) as u16, (v % 10000) as u16],
-        }.normalize();
+        BigInt::from(v as u128)
     }
 }
 
 impl From<i32> for BigInt {
     fn from(v: i32) -> BigInt {
-        let (abs, negative) = if v < 0 {
-            (u32::MAX - v as u32 + 1, true)
-        } else {
-            (v as u32, false)
-        };
-        return BigInt {
-            negative,
-            weight: 1,
-            digits: vec![(abs / 10000) as u16, (abs % 10000) as u16],
-        }.normalize();
+        BigInt::from(v as i128)
+    }
+}
+
+/// Only integral values are accepted
+///
+/// Value is converted using the shortest decimal representation that
+/// round-trips to the same float, i.e. `1e20_f64` is converted to
+/// `100000000000000000000` rather than to the exact binary value.
+impl TryFrom<f64> for BigInt {
+    type Error = OutOfRange;
+    fn try_from(v: f64) -> Result<BigInt, Self::Error> {
+        if !v.is_finite() || v.fract() != 0.0 {
+            return Err(OutOfRange);
+        }
+        let (negative, digits, exp) = float_digits(v);
+        if exp < 0 {
+            return Err(OutOfRange);
+        }
+        let (weight, digits) = base10000(&digits, exp)?;
+        Ok(BigInt { negative, weight, digits }.normalize())
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.digits.is_empty() {
+            return "0".fmt(f);
+        }
+        let mut buf = String::with_capacity(self.digits.len()*4 + 1);
+        if self.negative {
+            buf.push('-');
+        }
+        for idx in 0..=self.weight.max(0) as usize {
+            let digit = self.digits.get(idx).cloned().unwrap_or(0);
+            if idx == 0 {
+                buf.push_str(&digit.to_string());
+            } else {
+                buf.push_str(&format!("{:04}", digit));
+            }
+        }
+        f.write_str(&buf)
+    }
+}
+
+impl fmt::LowerExp for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_scientific(f, self.negative, self.weight, &self.digits)
+    }
+}
+
+/// Scale of the value is not preserved: `1.50` is formatted as `1.5e0`
+impl fmt::LowerExp for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_scientific(f, self.negative, self.weight, &self.digits)
+    }
+}
+
+fn write_scientific(f: &mut fmt::Formatter,
+                    negative: bool, weight: i16, digits: &[u16])
+    -> fmt::Result
+{
+    let mut text = String::with_capacity(digits.len()*4);
+    for digit in digits {
+        text.push_str(&format!("{:04}", digit));
+    }
+    let leading = text.bytes().take_while(|&c| c == b'0').count();
+    let significant = text[leading..].trim_end_matches('0');
+    if significant.is_empty() {
+        return f.write_str("0e0");
+    }
+    let exp = weight as i64 * 4 + 3 - leading as i64;
+    if negative {
+        f.write_str("-")?;
+    }
+    f.write_str(&significant[..1])?;
+    if significant.len() > 1 {
+        write!(f, ".{}", &significant[1..])?;
+    }
+    write!(f, "e{}", exp)
+}
+
+/// Returns sign, decimal digits and exponent of the shortest decimal
+/// representation of a finite float
+fn float_digits(v: f64) -> (bool, Vec<u8>, i64) {
+    // LowerExp formats shortest representation that round-trips,
+    // like `1.2345e-7`
+    let text = format!("{:e}", v.abs());
+    let mut parts = text.splitn(2, 'e');
+    let mantissa = parts.next().unwrap_or("0");
+    let exp: i64 = parts.next().and_then(|e| e.parse().ok()).unwrap_or(0);
+    let mut digits = Vec::with_capacity(mantissa.len());
+    let mut fraction = 0;
+    let mut in_fraction = false;
+    for c in mantissa.bytes() {
+        match c {
+            b'.' => in_fraction = true,
+            b'0'..=b'9' => {
+                digits.push(c - b'0');
+                if in_fraction {
+                    fraction += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    (v.is_sign_negative(), digits, exp - fraction)
+}
+
+/// Converts decimal digits (most significant first) multiplied by
+/// `10^exp` into weight and base 10000 digits
+fn base10000(digits: &[u8], exp: i64) -> Result<(i16, Vec<u16>), OutOfRange>
+{
+    if digits.is_empty() {
+        return Ok((0, Vec::new()));
     }
+    let last = exp.checked_add(digits.len() as i64 - 1).ok_or(OutOfRange)?;
+    let top = last.div_euclid(4);
+    let bottom = exp.div_euclid(4);
+    let weight = i16::try_from(top)?;
+    let mut result = vec![0u16; (top - bottom + 1) as usize];
+    for (idx, &digit) in digits.iter().enumerate() {
+        let power = exp + (digits.len() - 1 - idx) as i64;
+        let group = (top - power.div_euclid(4)) as usize;
+        result[group] += digit as u16 * 10u16.pow(power.rem_euclid(4) as u32);
+    }
+    Ok((weight, result))
 }
 
 #[cfg(feature="num-bigint")]
@@ -262,7 +807,6 @@ impl std::convert::TryFrom<num_bigint::BigInt> for BigInt {
 }
 
 impl Decimal {
-    #[allow(dead_code)]  // isn't used when BigDecimal is disabled
     fn normalize(mut self) -> Decimal {
         while let Some(0) = self.digits.last() {
             self.digits.pop();
@@ -271,10 +815,90 @@ impl Decimal {
             self.digits.remove(0);
             self.weight -= 1;
         }
+        if self.digits.is_empty() {
+            self.negative = false;
+            self.weight = 0;
+        }
         return self
     }
 }
 
+impl From<BigInt> for Decimal {
+    fn from(v: BigInt) -> Decimal {
+        Decimal {
+            negative: v.negative,
+            weight: v.weight,
+            decimal_digits: 0,
+            digits: v.digits,
+        }
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(v: i64) -> Decimal {
+        BigInt::from(v).into()
+    }
+}
+
+impl From<u64> for Decimal {
+    fn from(v: u64) -> Decimal {
+        BigInt::from(v).into()
+    }
+}
+
+/// Value is converted using the shortest decimal representation that
+/// round-trips to the same float, i.e. `0.1_f64` is converted to `0.1`
+impl TryFrom<f64> for Decimal {
+    type Error = OutOfRange;
+    fn try_from(v: f64) -> Result<Decimal, Self::Error> {
+        if !v.is_finite() {
+            return Err(OutOfRange);
+        }
+        let (negative, digits, exp) = float_digits(v);
+        let (weight, digits) = base10000(&digits, exp)?;
+        let decimal_digits = if exp < 0 { u16::try_from(-exp)? } else { 0 };
+        Ok(Decimal { negative, weight, decimal_digits, digits }.normalize())
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut buf = String::with_capacity(
+            self.digits.len()*4 + self.decimal_digits as usize + 2);
+        if self.negative {
+            buf.push('-');
+        }
+        if self.weight < 0 || self.digits.is_empty() {
+            buf.push('0');
+        } else {
+            for idx in 0..=self.weight as usize {
+                let digit = self.digits.get(idx).cloned().unwrap_or(0);
+                if idx == 0 {
+                    buf.push_str(&digit.to_string());
+                } else {
+                    buf.push_str(&format!("{:04}", digit));
+                }
+            }
+        }
+        if self.decimal_digits > 0 {
+            buf.push('.');
+            let start = buf.len();
+            let groups = (self.decimal_digits as i64 + 3) / 4;
+            for group in 1..=groups {
+                let idx = self.weight as i64 + group;
+                let digit = if idx >= 0 {
+                    self.digits.get(idx as usize).cloned().unwrap_or(0)
+                } else {
+                    0
+                };
+                buf.push_str(&format!("{:04}", digit));
+            }
+            buf.truncate(start + self.decimal_digits as usize);
+        }
+        f.write_str(&buf)
+    }
+}
+
 #[cfg(feature="bigdecimal")]
 impl std::convert::TryFrom<bigdecimal::BigDecimal> for Decimal {
     type Error = OutOfRange;
@@ -376,23 +1000,112 @@ impl Into<bigdecimal::BigDecimal> for &Decimal {
     }
 }
 
-#[cfg(feature="num-bigint")]
-impl Into<num_bigint::BigInt> for BigInt {
-    fn into(self) -> num_bigint::BigInt {
-        (&self).into()
+#[cfg(feature="rust_decimal")]
+impl From<rust_decimal::Decimal> for Decimal {
+    fn from(dec: rust_decimal::Decimal) -> Decimal {
+        (&dec).into()
     }
 }
 
-#[cfg(feature="num-bigint")]
-impl Into<num_bigint::BigInt> for &BigInt {
-    fn into(self) -> num_bigint::BigInt {
-        use num_bigint::BigInt;
-        use num_traits::pow;
-
-        let mut r = BigInt::from(0);
-        for &digit in &self.digits {
-            r *= 10000;
-            r += digit;
+#[cfg(feature="rust_decimal")]
+impl From<&rust_decimal::Decimal> for Decimal {
+    fn from(dec: &rust_decimal::Decimal) -> Decimal {
+        let mantissa = dec.mantissa();
+        let scale = dec.scale();  // at most 28
+        let groups = (scale + 3) / 4;
+        // can't overflow: mantissa is at most 96 bits
+        let mut val = mantissa.abs() as u128 * 10u128.pow(groups*4 - scale);
+        if val == 0 {
+            return Decimal {
+                negative: false,
+                weight: 0,
+                decimal_digits: scale as u16,
+                digits: Vec::new(),
+            };
+        }
+        let mut digits = Vec::new();
+        while val > 0 {
+            digits.push((val % 10000) as u16);
+            val /= 10000;
+        }
+        digits.reverse();
+        Decimal {
+            negative: mantissa < 0,
+            weight: digits.len() as i16 - groups as i16 - 1,
+            decimal_digits: scale as u16,
+            digits,
+        }.normalize()
+    }
+}
+
+#[cfg(feature="rust_decimal")]
+impl std::convert::TryFrom<Decimal> for rust_decimal::Decimal {
+    type Error = RustDecimalError;
+    fn try_from(dec: Decimal) -> Result<rust_decimal::Decimal, Self::Error> {
+        std::convert::TryFrom::try_from(&dec)
+    }
+}
+
+#[cfg(feature="rust_decimal")]
+impl std::convert::TryFrom<&Decimal> for rust_decimal::Decimal {
+    type Error = RustDecimalError;
+    fn try_from(dec: &Decimal)
+        -> Result<rust_decimal::Decimal, Self::Error>
+    {
+        use RustDecimalError::*;
+
+        let mut val = 0u128;
+        for &digit in &dec.digits {
+            val = val.checked_mul(10000)
+                .and_then(|v| v.checked_add(digit as u128))
+                .ok_or(TooManyDigits)?;
+        }
+        // `val` holds value multiplied by `10^stored`
+        let stored = 4*(dec.digits.len() as i64 - dec.weight as i64 - 1);
+        let mut scale = dec.decimal_digits as i64;
+        if stored > scale {
+            // digits beyond display scale are truncated like in BigDecimal
+            for _ in scale..stored {
+                val /= 10;
+            }
+        } else {
+            for _ in stored..scale {
+                val = val.checked_mul(10).ok_or(TooManyDigits)?;
+            }
+        }
+        while scale > 28 && val % 10 == 0 {
+            val /= 10;
+            scale -= 1;
+        }
+        if scale > 28 {
+            return Err(ScaleTooLarge { scale: dec.decimal_digits });
+        }
+        if val >> 96 != 0 {
+            return Err(TooManyDigits);
+        }
+        Ok(rust_decimal::Decimal::from_parts(
+            val as u32, (val >> 32) as u32, (val >> 64) as u32,
+            dec.negative, scale as u32))
+    }
+}
+
+#[cfg(feature="num-bigint")]
+impl Into<num_bigint::BigInt> for BigInt {
+    fn into(self) -> num_bigint::BigInt {
+        (&self).into()
+    }
+}
+
+#[cfg(feature="num-bigint")]
+impl Into<num_bigint::BigInt> for &BigInt {
+    fn into(self) -> num_bigint::BigInt {
+        use num_bigint::BigInt;
+        use num_traits::pow;
+
+        let mut r = BigInt::from(0);
+        for &digit in &self.digits {
+            r *= 10000;
+            r += digit;
         }
         if (self.weight+1) as usize > self.digits.len() {
             r *= pow(BigInt::from(10000),
@@ -405,31 +1118,309 @@ impl Into<num_bigint::BigInt> for &BigInt {
     }
 }
 
+const MICROS_PER_DAY: i64 = 86400_000_000;
+// Days between 1970-01-01 and 2000-01-01
+const UNIX_TO_POSTGRES_DAYS: i64 = 10957;
+
+// Returns number of days since 2000-01-01 in proleptic gregorian calendar
+//
+// Algorithm by Howard Hinnant:
+// http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_ymd(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let yoe = year - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468 - UNIX_TO_POSTGRES_DAYS
+}
+
+// Inverse of `days_from_ymd`
+fn ymd_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + UNIX_TO_POSTGRES_DAYS + 719468;
+    let era = (if z >= 0 { z } else { z - 146096 }) / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+// Microseconds between 1970-01-01 and 2000-01-01
+const UNIX_TO_POSTGRES_MICROS: i64 = UNIX_TO_POSTGRES_DAYS * MICROS_PER_DAY;
+// Days from 2000-01-01 to 0001-01-01 and 9999-12-31, the range supported
+// by the database
+const MIN_DAYS: i64 = -730119;
+const MAX_DAYS: i64 = 2921939;
+const MIN_MICROS: i64 = MIN_DAYS * MICROS_PER_DAY;
+const MAX_MICROS: i64 = (MAX_DAYS + 1) * MICROS_PER_DAY - 1;
+
+impl Datetime {
+    /// 0001-01-01T00:00:00Z
+    pub const MIN: Datetime = Datetime { micros: MIN_MICROS };
+    /// 9999-12-31T23:59:59.999999Z
+    pub const MAX: Datetime = Datetime { micros: MAX_MICROS };
+
+    /// Creates datetime from microseconds since 2000-01-01T00:00:00Z
+    ///
+    /// Returns `OutOfRange` if the value is outside of `MIN..=MAX`.
+    pub fn try_from_micros(micros: i64) -> Result<Datetime, OutOfRange> {
+        if micros < MIN_MICROS || micros > MAX_MICROS {
+            return Err(OutOfRange);
+        }
+        Ok(Datetime { micros })
+    }
+    /// Creates datetime from microseconds since 1970-01-01T00:00:00Z
+    pub fn try_from_unix_micros(micros: i64) -> Result<Datetime, OutOfRange> {
+        Datetime::try_from_micros(
+            micros.checked_sub(UNIX_TO_POSTGRES_MICROS).ok_or(OutOfRange)?)
+    }
+    /// Creates datetime from microseconds since 1970-01-01T00:00:00Z
+    ///
+    /// Panics if the result is outside of `Datetime::MIN..=Datetime::MAX`,
+    /// use `try_from_unix_micros` to get an error instead
+    pub fn from_unix_micros(micros: i64) -> Datetime {
+        Datetime::try_from_unix_micros(micros)
+            .expect("datetime is out of range")
+    }
+    /// Returns number of microseconds since 1970-01-01T00:00:00Z
+    pub fn to_unix_micros(&self) -> i64 {
+        // can't overflow for values within `MIN..=MAX`
+        self.micros + UNIX_TO_POSTGRES_MICROS
+    }
+    /// Current time, truncated to microseconds
+    ///
+    /// Panics if the system clock is outside of `MIN..=MAX`
+    pub fn now() -> Datetime {
+        Datetime::now_with(&SystemClock)
+    }
+    /// Current time according to the `clock`
+    pub fn now_with<C: Clock + ?Sized>(clock: &C) -> Datetime {
+        clock.now()
+    }
+}
+
+impl DatetimeNanos {
+    /// Creates value from nanoseconds since 1970-01-01T00:00:00Z
+    pub fn try_from_unix_nanos(nanos: i128)
+        -> Result<DatetimeNanos, OutOfRange>
+    {
+        let micros = i64::try_from(nanos.div_euclid(1000))?;
+        Ok(DatetimeNanos {
+            datetime: Datetime::try_from_unix_micros(micros)?,
+            nanos: nanos.rem_euclid(1000) as u16,
+        })
+    }
+    /// Returns number of nanoseconds since 1970-01-01T00:00:00Z
+    pub fn to_unix_nanos(&self) -> i128 {
+        self.datetime.to_unix_micros() as i128 * 1000 + self.nanos as i128
+    }
+    /// Nanoseconds that don't fit into microsecond precision (0..1000)
+    pub fn submicro_nanos(&self) -> u16 {
+        self.nanos
+    }
+    /// Returns true if conversion to `Datetime` is lossless
+    pub fn is_exact(&self) -> bool {
+        self.nanos == 0
+    }
+    /// Converts to `Datetime`, failing if nanoseconds would be lost
+    pub fn to_exact(&self) -> Result<Datetime, PrecisionLoss> {
+        if self.nanos != 0 {
+            return Err(PrecisionLoss);
+        }
+        Ok(self.datetime)
+    }
+    /// Converts to `Datetime` dropping nanoseconds (rounds towards the past)
+    pub fn truncate(&self) -> Datetime {
+        self.datetime
+    }
+    /// Converts to `Datetime` rounding to the nearest microsecond
+    ///
+    /// Half a microsecond is rounded up, which fails on `Datetime::MAX`.
+    pub fn round(&self) -> Result<Datetime, OutOfRange> {
+        if self.nanos < 500 {
+            return Ok(self.datetime);
+        }
+        Datetime::try_from_micros(self.datetime.micros + 1)
+    }
+}
+
+impl From<Datetime> for DatetimeNanos {
+    fn from(datetime: Datetime) -> DatetimeNanos {
+        DatetimeNanos { datetime, nanos: 0 }
+    }
+}
+
+impl TryFrom<SystemTime> for DatetimeNanos {
+    type Error = OutOfRange;
+    fn try_from(time: SystemTime) -> Result<DatetimeNanos, Self::Error> {
+        let nanos = match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => i128::try_from(after.as_nanos())?,
+            Err(e) => -i128::try_from(e.duration().as_nanos())?,
+        };
+        DatetimeNanos::try_from_unix_nanos(nanos)
+    }
+}
+
+impl From<DatetimeNanos> for SystemTime {
+    fn from(dt: DatetimeNanos) -> SystemTime {
+        SystemTime::from(dt.datetime) +
+            std::time::Duration::from_nanos(dt.nanos as u64)
+    }
+}
+
 impl LocalDatetime {
+    /// 0001-01-01T00:00:00
+    pub const MIN: LocalDatetime = LocalDatetime { micros: MIN_MICROS };
+    /// 9999-12-31T23:59:59.999999
+    pub const MAX: LocalDatetime = LocalDatetime { micros: MAX_MICROS };
+
     pub fn from_micros(micros: i64) -> LocalDatetime {
         return LocalDatetime { micros }
     }
+    pub fn try_from_micros(micros: i64) -> Result<LocalDatetime, OutOfRange> {
+        if micros < MIN_MICROS || micros > MAX_MICROS {
+            return Err(OutOfRange);
+        }
+        Ok(LocalDatetime { micros })
+    }
+    pub fn new(date: LocalDate, time: LocalTime) -> LocalDatetime {
+        LocalDatetime {
+            micros: date.days as i64 * MICROS_PER_DAY + time.micros,
+        }
+    }
+    pub fn from_ymd_hms(year: i32, month: u32, day: u32,
+                        hour: u32, minute: u32, second: u32)
+        -> Result<LocalDatetime, OutOfRange>
+    {
+        let date = LocalDate::from_ymd(year, month, day)?;
+        let time = LocalTime::from_hms_micro(hour, minute, second, 0)?;
+        Ok(LocalDatetime::new(date, time))
+    }
+    pub fn date(&self) -> LocalDate {
+        LocalDate {
+            days: self.micros.div_euclid(MICROS_PER_DAY) as i32,
+        }
+    }
+    pub fn time(&self) -> LocalTime {
+        LocalTime {
+            micros: self.micros.rem_euclid(MICROS_PER_DAY),
+        }
+    }
 }
 
 impl LocalTime {
+    /// 00:00:00
+    pub const MIN: LocalTime = LocalTime { micros: 0 };
+    /// 23:59:59.999999
+    pub const MAX: LocalTime = LocalTime { micros: MICROS_PER_DAY - 1 };
+
     pub fn from_micros(micros: u64) -> LocalTime {
-        assert!(micros < 86400*1000_1000);
+        assert!(micros < MICROS_PER_DAY as u64);
         return LocalTime { micros: micros as i64  }
     }
+    pub fn try_from_micros(micros: u64) -> Result<LocalTime, OutOfRange> {
+        if micros >= MICROS_PER_DAY as u64 {
+            return Err(OutOfRange);
+        }
+        Ok(LocalTime { micros: micros as i64 })
+    }
+    pub fn from_hms_micro(hour: u32, minute: u32, second: u32, micro: u32)
+        -> Result<LocalTime, OutOfRange>
+    {
+        if hour > 23 || minute > 59 || second > 59 || micro > 999_999 {
+            return Err(OutOfRange);
+        }
+        let seconds = (hour as i64 * 60 + minute as i64) * 60 + second as i64;
+        Ok(LocalTime { micros: seconds * 1000_000 + micro as i64 })
+    }
+    pub fn hour(&self) -> u32 {
+        (self.micros / 3600_000_000) as u32
+    }
+    pub fn minute(&self) -> u32 {
+        (self.micros / 60_000_000 % 60) as u32
+    }
+    pub fn second(&self) -> u32 {
+        (self.micros / 1000_000 % 60) as u32
+    }
+    pub fn microsecond(&self) -> u32 {
+        (self.micros % 1000_000) as u32
+    }
 }
 
 impl LocalDate {
+    /// 0001-01-01
+    pub const MIN: LocalDate = LocalDate { days: MIN_DAYS as i32 };
+    /// 9999-12-31
+    pub const MAX: LocalDate = LocalDate { days: MAX_DAYS as i32 };
+
     pub fn from_days(days: i32) -> LocalDate {
         return LocalDate { days }
     }
+    pub fn try_from_days(days: i32) -> Result<LocalDate, OutOfRange> {
+        if (days as i64) < MIN_DAYS || days as i64 > MAX_DAYS {
+            return Err(OutOfRange);
+        }
+        Ok(LocalDate { days })
+    }
+    pub fn from_ymd(year: i32, month: u32, day: u32)
+        -> Result<LocalDate, OutOfRange>
+    {
+        let year = year as i64;
+        if year < 1 || year > 9999 ||
+            day < 1 || day > days_in_month(year, month)
+        {
+            return Err(OutOfRange);
+        }
+        Ok(LocalDate { days: days_from_ymd(year, month, day) as i32 })
+    }
+    pub fn year(&self) -> i32 {
+        ymd_from_days(self.days as i64).0 as i32
+    }
+    pub fn month(&self) -> u32 {
+        ymd_from_days(self.days as i64).1
+    }
+    pub fn day(&self) -> u32 {
+        ymd_from_days(self.days as i64).2
+    }
+    /// Current date in UTC
+    ///
+    /// Panics if the system clock is outside of `MIN..=MAX`
+    pub fn today() -> LocalDate {
+        LocalDate::today_with(&SystemClock)
+    }
+    /// Current date in UTC according to the `clock`
+    pub fn today_with<C: Clock + ?Sized>(clock: &C) -> LocalDate {
+        LocalDate {
+            days: clock.now().micros.div_euclid(MICROS_PER_DAY) as i32,
+        }
+    }
 }
 
 #[cfg(feature="chrono")]
 impl std::convert::TryInto<chrono::naive::NaiveDateTime> for &LocalDatetime {
     type Error = OutOfRange;
     fn try_into(self) -> Result<chrono::naive::NaiveDateTime, Self::Error> {
-        chrono::naive::NaiveDateTime::from_timestamp_opt(self.micros/1000_000,
-            ((self.micros % 1000_000)*1000) as u32)
+        let micros = self.micros.checked_add(UNIX_TO_POSTGRES_MICROS)
+            .ok_or(OutOfRange)?;
+        chrono::DateTime::from_timestamp(
+            micros.div_euclid(1000_000),
+            (micros.rem_euclid(1000_000)*1000) as u32)
+        .map(|d| d.naive_utc())
         .ok_or(OutOfRange)
     }
 }
@@ -440,13 +1431,13 @@ impl std::convert::TryFrom<&chrono::naive::NaiveDateTime> for LocalDatetime {
     fn try_from(d: &chrono::naive::NaiveDateTime)
         -> Result<LocalDatetime, Self::Error>
     {
+        let d = d.and_utc();
         let secs = d.timestamp();
         let micros = d.timestamp_subsec_micros();
-        Ok(LocalDatetime {
-            micros: secs.checked_mul(1_000_000)
-                .and_then(|x| x.checked_add(micros as i64))
-                .ok_or(OutOfRange)?,
-        })
+        LocalDatetime::try_from_micros(secs.checked_mul(1_000_000)
+            .and_then(|x| x.checked_add(micros as i64))
+            .and_then(|x| x.checked_sub(UNIX_TO_POSTGRES_MICROS))
+            .ok_or(OutOfRange)?)
     }
 }
 
@@ -477,9 +1468,10 @@ impl std::convert::TryInto<chrono::naive::NaiveDate> for &LocalDate {
 #[cfg(feature="chrono")]
 impl Into<chrono::naive::NaiveTime> for &LocalTime {
     fn into(self) -> chrono::naive::NaiveTime {
-        chrono::naive::NaiveTime::from_num_seconds_from_midnight(
+        chrono::naive::NaiveTime::from_num_seconds_from_midnight_opt(
             (self.micros / 1000_000) as u32,
             ((self.micros % 1000_000) * 1000) as u32)
+        .expect("local time is always valid")
     }
 }
 
@@ -543,6 +1535,224 @@ impl From<chrono::naive::NaiveTime> for LocalTime {
     }
 }
 
+#[cfg(feature="chrono")]
+impl std::convert::TryFrom<&chrono::DateTime<chrono::Utc>> for Datetime {
+    type Error = OutOfRange;
+    fn try_from(d: &chrono::DateTime<chrono::Utc>)
+        -> Result<Datetime, Self::Error>
+    {
+        let secs = d.timestamp();
+        let micros = d.timestamp_subsec_micros();
+        Datetime::try_from_unix_micros(secs.checked_mul(1_000_000)
+            .and_then(|x| x.checked_add(micros as i64))
+            .ok_or(OutOfRange)?)
+    }
+}
+
+#[cfg(feature="chrono")]
+impl std::convert::TryFrom<chrono::DateTime<chrono::Utc>> for Datetime {
+    type Error = OutOfRange;
+    fn try_from(d: chrono::DateTime<chrono::Utc>)
+        -> Result<Datetime, Self::Error>
+    {
+        std::convert::TryFrom::try_from(&d)
+    }
+}
+
+#[cfg(feature="chrono")]
+impl From<&Datetime> for chrono::DateTime<chrono::Utc> {
+    fn from(dt: &Datetime) -> chrono::DateTime<chrono::Utc> {
+        let micros = dt.to_unix_micros();
+        chrono::DateTime::from_timestamp(
+            micros.div_euclid(1000_000),
+            (micros.rem_euclid(1000_000)*1000) as u32)
+        // any value within `Datetime::MIN..=Datetime::MAX` fits chrono
+        .expect("datetime is within chrono range")
+    }
+}
+
+#[cfg(feature="chrono")]
+impl From<Datetime> for chrono::DateTime<chrono::Utc> {
+    fn from(dt: Datetime) -> chrono::DateTime<chrono::Utc> {
+        From::from(&dt)
+    }
+}
+
+#[cfg(feature="chrono")]
+impl std::convert::TryFrom<&chrono::DateTime<chrono::Utc>> for DatetimeNanos {
+    type Error = OutOfRange;
+    fn try_from(d: &chrono::DateTime<chrono::Utc>)
+        -> Result<DatetimeNanos, Self::Error>
+    {
+        let nanos = d.timestamp() as i128 * 1000_000_000
+            + d.timestamp_subsec_nanos() as i128;
+        DatetimeNanos::try_from_unix_nanos(nanos)
+    }
+}
+
+#[cfg(feature="chrono")]
+impl std::convert::TryFrom<chrono::DateTime<chrono::Utc>> for DatetimeNanos {
+    type Error = OutOfRange;
+    fn try_from(d: chrono::DateTime<chrono::Utc>)
+        -> Result<DatetimeNanos, Self::Error>
+    {
+        std::convert::TryFrom::try_from(&d)
+    }
+}
+
+#[cfg(feature="chrono")]
+impl From<DatetimeNanos> for chrono::DateTime<chrono::Utc> {
+    fn from(dt: DatetimeNanos) -> chrono::DateTime<chrono::Utc> {
+        let nanos = dt.to_unix_nanos();
+        chrono::DateTime::from_timestamp(
+            nanos.div_euclid(1000_000_000) as i64,
+            nanos.rem_euclid(1000_000_000) as u32)
+        // any value within `Datetime::MIN..=Datetime::MAX` fits chrono
+        .expect("datetime is within chrono range")
+    }
+}
+
+#[cfg(feature="chrono")]
+impl std::convert::TryFrom<&chrono::Duration> for Duration {
+    type Error = OutOfRange;
+    /// Nanoseconds are rounded toward negative infinity, like in datetime
+    /// conversions
+    fn try_from(d: &chrono::Duration) -> Result<Duration, Self::Error> {
+        // `num_microseconds` rounds toward zero
+        let mut micros = d.num_microseconds().ok_or(OutOfRange)?;
+        if *d < chrono::Duration::microseconds(micros) {
+            micros = micros.checked_sub(1).ok_or(OutOfRange)?;
+        }
+        Ok(Duration::from_micros(micros))
+    }
+}
+
+#[cfg(feature="chrono")]
+impl std::convert::TryFrom<chrono::Duration> for Duration {
+    type Error = OutOfRange;
+    fn try_from(d: chrono::Duration) -> Result<Duration, Self::Error> {
+        std::convert::TryFrom::try_from(&d)
+    }
+}
+
+#[cfg(feature="chrono")]
+impl From<&Duration> for chrono::Duration {
+    fn from(d: &Duration) -> chrono::Duration {
+        // chrono duration holds up to `i64::max_value()` milliseconds
+        chrono::Duration::microseconds(d.micros)
+    }
+}
+
+#[cfg(feature="chrono")]
+impl From<Duration> for chrono::Duration {
+    fn from(d: Duration) -> chrono::Duration {
+        From::from(&d)
+    }
+}
+
+#[cfg(feature="time")]
+impl std::convert::TryFrom<time::Date> for LocalDate {
+    type Error = OutOfRange;
+    fn try_from(d: time::Date) -> Result<LocalDate, Self::Error> {
+        LocalDate::from_ymd(d.year(), d.month() as u32, d.day() as u32)
+    }
+}
+
+#[cfg(feature="time")]
+impl std::convert::TryFrom<LocalDate> for time::Date {
+    type Error = OutOfRange;
+    fn try_from(d: LocalDate) -> Result<time::Date, Self::Error> {
+        let (year, month, day) = ymd_from_days(d.days as i64);
+        time::Date::try_from_ymd(year as i32, month as u8, day as u8)
+            .map_err(|_| OutOfRange)
+    }
+}
+
+#[cfg(feature="time")]
+impl From<time::Time> for LocalTime {
+    /// Nanoseconds are truncated
+    fn from(t: time::Time) -> LocalTime {
+        let seconds = (t.hour() as i64 * 60 + t.minute() as i64) * 60
+            + t.second() as i64;
+        LocalTime { micros: seconds * 1000_000 + t.microsecond() as i64 }
+    }
+}
+
+#[cfg(feature="time")]
+impl From<LocalTime> for time::Time {
+    fn from(t: LocalTime) -> time::Time {
+        time::Time::try_from_hms_micro(t.hour() as u8, t.minute() as u8,
+                                       t.second() as u8, t.microsecond())
+            .expect("local time is always valid")
+    }
+}
+
+#[cfg(feature="time")]
+impl std::convert::TryFrom<time::PrimitiveDateTime> for LocalDatetime {
+    type Error = OutOfRange;
+    fn try_from(d: time::PrimitiveDateTime)
+        -> Result<LocalDatetime, Self::Error>
+    {
+        Ok(LocalDatetime::new(LocalDate::try_from(d.date())?,
+                              d.time().into()))
+    }
+}
+
+#[cfg(feature="time")]
+impl std::convert::TryFrom<LocalDatetime> for time::PrimitiveDateTime {
+    type Error = OutOfRange;
+    fn try_from(d: LocalDatetime)
+        -> Result<time::PrimitiveDateTime, Self::Error>
+    {
+        Ok(time::PrimitiveDateTime::new(
+            time::Date::try_from(d.date())?,
+            d.time().into()))
+    }
+}
+
+#[cfg(feature="time")]
+impl std::convert::TryFrom<time::OffsetDateTime> for Datetime {
+    type Error = OutOfRange;
+    fn try_from(d: time::OffsetDateTime) -> Result<Datetime, Self::Error> {
+        let utc = d.to_offset(time::UtcOffset::UTC);
+        let local = LocalDatetime::try_from(
+            time::PrimitiveDateTime::new(utc.date(), utc.time()))?;
+        // both types share the same epoch and range
+        Ok(Datetime { micros: local.micros })
+    }
+}
+
+#[cfg(feature="time")]
+impl std::convert::TryFrom<Datetime> for time::OffsetDateTime {
+    type Error = OutOfRange;
+    fn try_from(d: Datetime) -> Result<time::OffsetDateTime, Self::Error> {
+        let local = LocalDatetime { micros: d.micros };
+        Ok(time::PrimitiveDateTime::try_from(local)?.assume_utc())
+    }
+}
+
+#[cfg(feature="time")]
+impl std::convert::TryFrom<time::Duration> for Duration {
+    type Error = OutOfRange;
+    /// Nanoseconds are rounded toward negative infinity, like in datetime
+    /// conversions
+    fn try_from(d: time::Duration) -> Result<Duration, Self::Error> {
+        // `whole_microseconds` rounds toward zero
+        let mut micros = d.whole_microseconds();
+        if d.subsec_nanoseconds() % 1000 < 0 {
+            micros -= 1;
+        }
+        Ok(Duration { micros: i64::try_from(micros)? })
+    }
+}
+
+#[cfg(feature="time")]
+impl From<Duration> for time::Duration {
+    fn from(d: Duration) -> time::Duration {
+        time::Duration::microseconds(d.micros)
+    }
+}
+
 #[cfg(test)]
 #[allow(unused_imports)]  // because of optional tests
 mod test {
@@ -606,6 +1816,199 @@ mod test {
                    Trg::new(9223372036854, 775808000));
     }
 
+    #[test]
+    fn calendar() {
+        use super::{days_from_ymd, ymd_from_days};
+
+        assert_eq!(days_from_ymd(2000, 1, 1), 0);
+        assert_eq!(days_from_ymd(2019, 12, 23), 7296);
+        assert_eq!(days_from_ymd(1970, 1, 1), -10957);
+        assert_eq!(days_from_ymd(1, 1, 1), -730119);
+        assert_eq!(ymd_from_days(0), (2000, 1, 1));
+        assert_eq!(ymd_from_days(7296), (2019, 12, 23));
+        assert_eq!(ymd_from_days(-730119), (1, 1, 1));
+        assert_eq!(ymd_from_days(59), (2000, 2, 29));
+        for days in -800_000..3_000_000 {
+            let (y, m, d) = ymd_from_days(days);
+            assert_eq!(days_from_ymd(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn components() {
+        use super::{Datetime, LocalDatetime, LocalDate, LocalTime};
+
+        let time = LocalTime::from_hms_micro(14, 7, 40, 392449).unwrap();
+        assert_eq!(time, LocalTime { micros: 50860392449 });
+        assert_eq!((time.hour(), time.minute(), time.second()), (14, 7, 40));
+        assert_eq!(time.microsecond(), 392449);
+        assert!(LocalTime::from_hms_micro(24, 0, 0, 0).is_err());
+        assert!(LocalTime::from_hms_micro(0, 0, 0, 1000_000).is_err());
+
+        let date = LocalDate::from_ymd(2019, 12, 23).unwrap();
+        assert_eq!(date, LocalDate { days: 7296 });
+        assert_eq!((date.year(), date.month(), date.day()), (2019, 12, 23));
+        assert!(LocalDate::from_ymd(2019, 2, 29).is_err());
+        assert!(LocalDate::from_ymd(0, 1, 1).is_err());
+
+        let dt = LocalDatetime::from_ymd_hms(1999, 12, 31, 23, 59, 59)
+            .unwrap();
+        assert_eq!(dt, LocalDatetime { micros: -1000_000 });
+        assert_eq!(dt.date(), LocalDate::from_ymd(1999, 12, 31).unwrap());
+        assert_eq!(dt.time(),
+                   LocalTime::from_hms_micro(23, 59, 59, 0).unwrap());
+        assert_eq!(LocalDatetime::new(dt.date(), dt.time()), dt);
+
+        assert_eq!(Datetime::from_unix_micros(946684800_000_000),
+                   Datetime { micros: 0 });
+        assert_eq!(Datetime { micros: 1 }.to_unix_micros(),
+                   946684800_000_001);
+    }
+
+    #[test]
+    fn ranges() {
+        use super::{Datetime, LocalDatetime, LocalDate, LocalTime, Duration};
+
+        assert_eq!(LocalDate::MIN, LocalDate::from_ymd(1, 1, 1).unwrap());
+        assert_eq!(LocalDate::MAX, LocalDate::from_ymd(9999, 12, 31).unwrap());
+        assert_eq!(LocalDatetime::MIN,
+                   LocalDatetime::from_ymd_hms(1, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(LocalDatetime::MAX.date(), LocalDate::MAX);
+        assert_eq!(LocalDatetime::MAX.time(), LocalTime::MAX);
+        assert!(LocalDate::try_from_days(LocalDate::MAX.days + 1).is_err());
+        assert!(LocalDate::try_from_days(LocalDate::MIN.days).is_ok());
+        assert!(LocalTime::try_from_micros(86400_000_000).is_err());
+        assert!(LocalTime::try_from_micros(86399_999_999).is_ok());
+        assert!(LocalDatetime::try_from_micros(i64::min_value()).is_err());
+        assert!(Datetime::try_from_micros(Datetime::MAX.micros + 1).is_err());
+        assert!(Datetime::try_from_unix_micros(i64::min_value()).is_err());
+        assert_eq!(Datetime::MAX.checked_add(Duration::from_micros(1)), None);
+        assert_eq!(Datetime::MIN.checked_sub(Duration::from_micros(1)), None);
+        assert_eq!("0001-01-01T00:00:00Z".parse::<Datetime>().unwrap(),
+                   Datetime::MIN);
+        assert!("0001-01-01T00:00:00+01:00".parse::<Datetime>().is_err());
+    }
+
+    #[test]
+    fn duration_std_conversion() {
+        use super::Duration as Src;
+        use std::time::Duration as Trg;
+
+        assert_eq!(Trg::try_from(Src::from_micros(1500)).ok(),
+                   Some(Trg::new(0, 1500_000)));
+        assert!(Trg::try_from(Src::from_micros(-1)).is_err());
+        assert_eq!(Src::from_micros(-1).to_std_saturating(), Trg::new(0, 0));
+        assert_eq!(Src::try_from(Trg::new(1, 999)).ok(),
+                   Some(Src::from_micros(1000_000)));
+        assert!(Src::try_from(Trg::new(u64::max_value(), 0)).is_err());
+        assert_eq!(Src::MAX.saturating_add(Src::from_micros(1)), Src::MAX);
+        assert_eq!(Src::MIN.saturating_sub(Src::from_micros(1)), Src::MIN);
+        assert_eq!(Src::ZERO.saturating_sub(Src::MAX),
+                   Src::from_micros(-i64::max_value()));
+    }
+
+    #[test]
+    fn datetime_arithmetic() {
+        use super::{Datetime, Duration, LocalDatetime};
+
+        let dt = Datetime { micros: 1_000_000 };
+        let hour = Duration::from_micros(3600_000_000);
+        assert_eq!(dt + hour, Datetime { micros: 3601_000_000 });
+        assert_eq!(dt - hour, Datetime { micros: -3599_000_000 });
+        assert_eq!((dt + hour) - dt, hour);
+        assert_eq!(dt - (dt + hour), -hour);
+        assert_eq!(hour + hour - hour, hour);
+        assert_eq!(dt.checked_add(Duration::from_micros(i64::max_value())),
+                   None);
+        assert_eq!(Duration::from_micros(i64::min_value()).checked_neg(),
+                   None);
+
+        let local = LocalDatetime { micros: 0 };
+        assert_eq!(local + hour - local, hour);
+        assert_eq!(local.checked_sub(Duration::from_micros(1)),
+                   Some(LocalDatetime { micros: -1 }));
+    }
+
+    #[test]
+    fn datetime_system_time() -> Result<(), Box<dyn std::error::Error>> {
+        use std::time::{SystemTime, UNIX_EPOCH, Duration};
+        use super::Datetime;
+
+        let time = UNIX_EPOCH + Duration::new(1577109148, 156903000);
+        let dt = Datetime::try_from(time)?;
+        assert_eq!(dt, Datetime { micros: 630424348156903 });
+        assert_eq!(SystemTime::from(dt), time);
+        assert_eq!(Datetime::try_from(UNIX_EPOCH)?,
+                   Datetime { micros: -946684800_000_000 });
+        assert_eq!(SystemTime::from(Datetime { micros: -946684800_000_000 }),
+                   UNIX_EPOCH);
+        Ok(())
+    }
+
+    #[test]
+    fn now() {
+        use super::{Datetime, LocalDate};
+
+        let before = Datetime::now();
+        let today = LocalDate::today();
+        let after = Datetime::now();
+        assert!(before <= after);
+        assert!(before > Datetime::from_unix_micros(1577836800_000_000));
+        assert!(today.year() >= 2020);
+    }
+
+    #[test]
+    fn datetime_nanos() -> Result<(), Box<dyn std::error::Error>> {
+        use std::time::{SystemTime, UNIX_EPOCH, Duration};
+        use super::{Datetime, DatetimeNanos};
+
+        let dt = DatetimeNanos::try_from_unix_nanos(1_500_000_499)?;
+        assert_eq!(dt.submicro_nanos(), 499);
+        assert!(dt.to_exact().is_err());
+        assert_eq!(dt.truncate(), Datetime::from_unix_micros(1_500_000));
+        assert_eq!(dt.round()?, Datetime::from_unix_micros(1_500_000));
+        let dt = DatetimeNanos::try_from_unix_nanos(1_500_000_500)?;
+        assert_eq!(dt.round()?, Datetime::from_unix_micros(1_500_001));
+
+        // negative values are truncated towards the past
+        let dt = DatetimeNanos::try_from_unix_nanos(-1)?;
+        assert_eq!(dt.truncate(), Datetime::from_unix_micros(-1));
+        assert_eq!(dt.submicro_nanos(), 999);
+        assert_eq!(dt.round()?, Datetime::from_unix_micros(0));
+        assert_eq!(dt.to_unix_nanos(), -1);
+
+        let time = UNIX_EPOCH + Duration::new(1577109148, 156903123);
+        let dt = DatetimeNanos::try_from(time)?;
+        assert_eq!(SystemTime::from(dt), time);
+        assert_eq!(dt.to_exact().ok(), None);
+        let exact = DatetimeNanos::from(dt.truncate());
+        assert!(exact.is_exact());
+        assert_eq!(exact.to_exact()?, dt.truncate());
+
+        let max = DatetimeNanos::try_from_unix_nanos(
+            Datetime::MAX.to_unix_micros() as i128 * 1000 + 999)?;
+        assert!(max.round().is_err());
+        assert!(DatetimeNanos::try_from_unix_nanos(
+            (Datetime::MAX.to_unix_micros() as i128 + 1) * 1000).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn approximate_size() {
+        use std::mem::size_of;
+        use super::Value;
+
+        let base = size_of::<Value>();
+        assert_eq!(Value::Int64(1).approximate_size(), base);
+        let s = String::with_capacity(100);
+        assert_eq!(Value::Str(s).approximate_size(), base + 100);
+        let mut items = Vec::with_capacity(2);
+        items.push(Value::Str(String::with_capacity(10)));
+        items.push(Value::Bool(true));
+        assert_eq!(Value::Array(items).approximate_size(),
+                   base + 2*base + 10);
+    }
+
     #[test]
     #[cfg(feature="chrono")]
     fn chrono_roundtrips() -> Result<(), Box<dyn std::error::Error>> {
@@ -625,6 +2028,250 @@ mod test {
             TryInto::<NaiveTime>::try_into(LocalTime::try_from(naive)?)?);
         Ok(())
     }
+
+    #[test]
+    #[cfg(feature="with-serde")]
+    fn json() -> Result<(), Box<dyn std::error::Error>> {
+        use super::Json;
+
+        let json = Json::new(r#"{"a": [1, 2]}"#.into())?;
+        assert_eq!(json.as_str(), r#"{"a": [1, 2]}"#);
+        assert!(Json::new("{".into()).is_err());
+        assert!(Json::new("1 2".into()).is_err());
+
+        let value: serde_json::Value = json.parse()?;
+        assert_eq!(value, serde_json::json!({"a": [1, 2]}));
+        assert_eq!(Json::from(value).as_str(), r#"{"a":[1,2]}"#);
+
+        let numbers: Vec<i32> = Json::new("[1, 2, 3]".into())?.parse()?;
+        assert_eq!(numbers, vec![1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn big_int_primitives() {
+        assert_eq!(BigInt::from(0u64), BigInt {
+            negative: false, weight: 0, digits: vec![] });
+        assert_eq!(BigInt::from(u64::max_value()), BigInt {
+            negative: false,
+            weight: 4,
+            digits: vec![1844, 6744, 737, 955, 1615],
+        });
+        assert_eq!(BigInt::from(i64::min_value()).to_string(),
+                   "-9223372036854775808");
+        assert_eq!(BigInt::from(u32::max_value()).to_string(), "4294967295");
+        assert_eq!(BigInt::from(i128::min_value()).to_string(),
+                   "-170141183460469231731687303715884105728");
+        assert_eq!(BigInt::from(-30000i32).to_string(), "-30000");
+        assert_eq!(BigInt::from(0i32).to_string(), "0");
+
+        assert_eq!(BigInt::try_from(1e20_f64).unwrap().to_string(),
+                   "100000000000000000000");
+        assert_eq!(BigInt::try_from(-1234.0).unwrap(), BigInt::from(-1234));
+        assert!(BigInt::try_from(0.5).is_err());
+        assert!(BigInt::try_from(std::f64::NAN).is_err());
+        assert!(BigInt::try_from(std::f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn decimal_primitives() {
+        assert_eq!(Decimal::from(-30001i64).to_string(), "-30001");
+        assert_eq!(Decimal::from(0i64).to_string(), "0");
+        assert_eq!(Decimal::try_from(0.1).unwrap(), Decimal {
+            negative: false,
+            weight: -1,
+            decimal_digits: 1,
+            digits: vec![1000],
+        });
+        assert_eq!(Decimal::try_from(0.1).unwrap().to_string(), "0.1");
+        assert_eq!(Decimal::try_from(-1.5e-7).unwrap().to_string(),
+                   "-0.00000015");
+        assert_eq!(Decimal::try_from(12345.678).unwrap().to_string(),
+                   "12345.678");
+        assert_eq!(Decimal::try_from(1e10).unwrap().to_string(),
+                   "10000000000");
+        assert!(Decimal::try_from(std::f64::NAN).is_err());
+    }
+
+    #[test]
+    fn decimal_display() {
+        let dec = Decimal {
+            negative: false,
+            weight: 0,
+            decimal_digits: 2,
+            digits: vec![42],
+        };
+        assert_eq!(dec.to_string(), "42.00");
+        let dec = Decimal {
+            negative: true,
+            weight: 1,
+            decimal_digits: 9,
+            digits: vec![1234, 5678, 9012, 3456, 7000],
+        };
+        assert_eq!(dec.to_string(), "-12345678.901234567");
+        let dec = Decimal {
+            negative: false,
+            weight: 25,
+            decimal_digits: 0,
+            digits: vec![1],
+        };
+        assert_eq!(dec.to_string(), format!("1{}", "0".repeat(100)));
+    }
+
+    #[test]
+    #[cfg(feature="rust_decimal")]
+    fn rust_decimal_roundtrips() -> Result<(), Box<dyn std::error::Error>> {
+        use super::RustDecimalError;
+
+        for text in &["0", "1.5", "-0.0001", "42.00", "10000000000",
+                      "12345678.901234567", "-0.0000000000000000000000000001",
+                      "79228162514264337593543950335"]
+        {
+            let orig = rust_decimal::Decimal::from_str(text)?;
+            let dec = Decimal::from(orig);
+            assert_eq!(rust_decimal::Decimal::try_from(&dec)?, orig);
+            assert_eq!(rust_decimal::Decimal::try_from(&dec)?.scale(),
+                       orig.scale());
+        }
+
+        let dec = Decimal::from(rust_decimal::Decimal::from_str("1.5")?);
+        assert_eq!(dec, Decimal {
+            negative: false,
+            weight: 0,
+            decimal_digits: 1,
+            digits: vec![1, 5000],
+        });
+        let dec = Decimal::from(rust_decimal::Decimal::from_str("-0.0001")?);
+        assert_eq!(dec, Decimal {
+            negative: true,
+            weight: -1,
+            decimal_digits: 4,
+            digits: vec![1],
+        });
+
+        let huge = Decimal {
+            negative: false,
+            weight: 8,
+            decimal_digits: 0,
+            digits: vec![100],
+        };
+        assert_eq!(rust_decimal::Decimal::try_from(&huge),
+                   Err(RustDecimalError::TooManyDigits));
+        let tiny = Decimal {
+            negative: false,
+            weight: -8,
+            decimal_digits: 32,
+            digits: vec![1],
+        };
+        assert_eq!(rust_decimal::Decimal::try_from(&tiny),
+                   Err(RustDecimalError::ScaleTooLarge { scale: 32 }));
+        // trailing zeros beyond maximum scale are dropped
+        let padded = Decimal {
+            negative: false,
+            weight: 0,
+            decimal_digits: 30,
+            digits: vec![1, 5000],
+        };
+        assert_eq!(rust_decimal::Decimal::try_from(&padded)?,
+                   rust_decimal::Decimal::from_str("1.5")?);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature="time")]
+    fn time_roundtrips() -> Result<(), Box<dyn std::error::Error>> {
+        use super::{Datetime, LocalDatetime, LocalDate, LocalTime, Duration};
+
+        let date = time::Date::try_from_ymd(2019, 12, 27)?;
+        let local = LocalDate::try_from(date)?;
+        assert_eq!(local, LocalDate::from_ymd(2019, 12, 27)?);
+        assert_eq!(time::Date::try_from(local)?, date);
+
+        let tm = time::Time::try_from_hms_micro(1, 2, 3, 123456)?;
+        let local = LocalTime::from(tm);
+        assert_eq!(local, LocalTime::from_hms_micro(1, 2, 3, 123456)?);
+        assert_eq!(time::Time::from(local), tm);
+
+        let prim = time::PrimitiveDateTime::new(date, tm);
+        let local = LocalDatetime::try_from(prim)?;
+        assert_eq!(local.date(), LocalDate::from_ymd(2019, 12, 27)?);
+        assert_eq!(time::PrimitiveDateTime::try_from(local)?, prim);
+
+        let odt = prim.assume_utc().to_offset(time::UtcOffset::hours(3));
+        let dt = Datetime::try_from(odt)?;
+        assert_eq!(dt, Datetime { micros: local.micros });
+        assert_eq!(time::OffsetDateTime::try_from(dt)?, odt);
+
+        let dur = time::Duration::seconds(-90);
+        assert_eq!(Duration::try_from(dur)?,
+                   Duration::from_micros(-90_000_000));
+        assert_eq!(time::Duration::from(Duration::from_micros(-90_000_000)),
+                   dur);
+        let dur = time::Duration::seconds(-90)
+            + time::Duration::nanoseconds(1500);
+        assert_eq!(Duration::try_from(dur)?,
+                   Duration::from_micros(-89_999_999));
+        assert_eq!(Duration::try_from(time::Duration::nanoseconds(1500))?,
+                   Duration::from_micros(1));
+        assert!(Duration::try_from(time::Duration::max_value()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature="chrono")]
+    fn chrono_epoch() -> Result<(), Box<dyn std::error::Error>> {
+        use std::convert::TryInto;
+        use super::LocalDatetime;
+        use chrono::naive::NaiveDateTime;
+
+        let naive = NaiveDateTime::from_str("2000-01-01T00:00:00")?;
+        assert_eq!(LocalDatetime::try_from(naive)?,
+                   LocalDatetime::from_micros(0));
+        let naive = NaiveDateTime::from_str("1999-12-31T23:59:59.5")?;
+        assert_eq!(LocalDatetime::try_from(naive)?,
+                   LocalDatetime::from_micros(-500_000));
+        assert_eq!(naive,
+            TryInto::<NaiveDateTime>::try_into(
+                LocalDatetime::from_micros(-500_000))?);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature="chrono")]
+    fn chrono_utc() -> Result<(), Box<dyn std::error::Error>> {
+        use super::{Datetime, Duration};
+        use chrono::{DateTime, Utc};
+
+        let utc = DateTime::<Utc>::from_str("2019-12-23T13:52:28.156903Z")?;
+        let dt = Datetime::try_from(utc)?;
+        assert_eq!(dt, Datetime { micros: 630424348156903 });
+        assert_eq!(DateTime::<Utc>::from(dt), utc);
+        assert_eq!(DateTime::<Utc>::from(Datetime::MIN),
+                   DateTime::<Utc>::from_str("0001-01-01T00:00:00Z")?);
+
+        let too_old = DateTime::<Utc>::from_str("0000-12-31T23:59:59Z")?;
+        assert!(Datetime::try_from(too_old).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature="chrono")]
+    fn chrono_duration() -> Result<(), Box<dyn std::error::Error>> {
+        use super::Duration;
+
+        let dur = chrono::Duration::seconds(-90)
+            + chrono::Duration::nanoseconds(1500);
+        assert_eq!(Duration::try_from(dur)?,
+                   Duration::from_micros(-89_999_999));
+        assert_eq!(Duration::try_from(chrono::Duration::nanoseconds(1500))?,
+                   Duration::from_micros(1));
+        assert_eq!(chrono::Duration::from(Duration::from_micros(-89_999_999)),
+                   chrono::Duration::microseconds(-89_999_999));
+        assert_eq!(chrono::Duration::from(Duration::MAX).num_microseconds(),
+                   Some(i64::max_value()));
+        assert!(Duration::try_from(chrono::Duration::MAX).is_err());
+        Ok(())
+    }
 }
 
 #[cfg(all(test, feature="num-bigint", feature="bigdecimal"))]
@@ -0,0 +1,193 @@
+//! Generators of random values for fuzzing and property tests
+//!
+//! Generated values are always valid, i.e. within the range supported by
+//! the database, so they can be encoded by the matching codec.
+use arbitrary::{Arbitrary, Unstructured, Result, Error};
+
+use super::{Value, Json};
+use super::{BigInt, Decimal, Duration};
+use super::{Datetime, LocalDatetime, LocalDate, LocalTime};
+use super::{MIN_DAYS, MAX_DAYS, MIN_MICROS, MAX_MICROS, MICROS_PER_DAY};
+
+/// Nesting of collections in generated `Value`
+const MAX_DEPTH: usize = 2;
+/// Number of base-10000 digits in generated numbers
+const MAX_DIGITS: usize = 8;
+
+fn digits(u: &mut Unstructured) -> Result<Vec<u16>> {
+    let len = u.int_in_range(0..=MAX_DIGITS)?;
+    (0..len).map(|_| u.int_in_range(0..=9999u16)).collect()
+}
+
+impl Arbitrary for BigInt {
+    fn arbitrary(u: &mut Unstructured) -> Result<BigInt> {
+        let negative = u.arbitrary()?;
+        let digits = digits(u)?;
+        // no fractional part, zeros are added at the end instead
+        let zeros = u.int_in_range(0..=4)?;
+        Ok(BigInt {
+            negative,
+            weight: digits.len() as i16 - 1 + zeros,
+            digits,
+        }.normalize())
+    }
+}
+
+impl Arbitrary for Decimal {
+    fn arbitrary(u: &mut Unstructured) -> Result<Decimal> {
+        let negative = u.arbitrary()?;
+        let digits = digits(u)?;
+        let weight = u.int_in_range(-4..=MAX_DIGITS as i16)?;
+        let fraction_groups = (digits.len() as i16 - weight - 1).max(0);
+        let extra_digits = u.int_in_range(0..=4)?;
+        Ok(Decimal {
+            negative,
+            weight,
+            decimal_digits: fraction_groups as u16 * 4 + extra_digits,
+            digits,
+        }.normalize())
+    }
+}
+
+impl Arbitrary for Datetime {
+    fn arbitrary(u: &mut Unstructured) -> Result<Datetime> {
+        Ok(Datetime { micros: u.int_in_range(MIN_MICROS..=MAX_MICROS)? })
+    }
+}
+
+impl Arbitrary for LocalDatetime {
+    fn arbitrary(u: &mut Unstructured) -> Result<LocalDatetime> {
+        Ok(LocalDatetime {
+            micros: u.int_in_range(MIN_MICROS..=MAX_MICROS)?,
+        })
+    }
+}
+
+impl Arbitrary for LocalDate {
+    fn arbitrary(u: &mut Unstructured) -> Result<LocalDate> {
+        Ok(LocalDate {
+            days: u.int_in_range(MIN_DAYS as i32..=MAX_DAYS as i32)?,
+        })
+    }
+}
+
+impl Arbitrary for LocalTime {
+    fn arbitrary(u: &mut Unstructured) -> Result<LocalTime> {
+        Ok(LocalTime { micros: u.int_in_range(0..=MICROS_PER_DAY-1)? })
+    }
+}
+
+impl Arbitrary for Duration {
+    fn arbitrary(u: &mut Unstructured) -> Result<Duration> {
+        Ok(Duration { micros: u.arbitrary()? })
+    }
+}
+
+/// Generates scalars, arrays, sets and tuples
+///
+/// Elements of arrays and sets are all of the same type. Objects, named
+/// tuples and enums are not generated as they need a shape or a type
+/// descriptor to be meaningful.
+impl Arbitrary for Value {
+    /// Returns `Value::Nothing` if data runs out before the first value
+    fn arbitrary(u: &mut Unstructured) -> Result<Value> {
+        match value(u, 0) {
+            Err(Error::NotEnoughData) => Ok(Value::Nothing),
+            res => res,
+        }
+    }
+}
+
+const SCALARS: u8 = 16;
+
+fn scalar(u: &mut Unstructured, kind: u8) -> Result<Value> {
+    use Value as V;
+    Ok(match kind {
+        0 => V::Uuid(uuid::Uuid::from_u128(u.arbitrary()?)),
+        1 => V::Str(u.arbitrary()?),
+        2 => V::Bytes(u.arbitrary()?),
+        3 => V::Int16(u.arbitrary()?),
+        4 => V::Int32(u.arbitrary()?),
+        5 => V::Int64(u.arbitrary()?),
+        6 => V::Float32(u.arbitrary()?),
+        7 => V::Float64(u.arbitrary()?),
+        8 => V::BigInt(u.arbitrary()?),
+        9 => V::Decimal(u.arbitrary()?),
+        10 => V::Bool(u.arbitrary()?),
+        11 => V::Datetime(u.arbitrary()?),
+        12 => V::LocalDatetime(u.arbitrary()?),
+        13 => V::LocalDate(u.arbitrary()?),
+        14 => V::LocalTime(u.arbitrary()?),
+        15 => V::Duration(u.arbitrary()?),
+        _ => {
+            let num: i64 = u.arbitrary()?;
+            V::Json(Json::new_unchecked(num.to_string()))
+        }
+    })
+}
+
+fn value(u: &mut Unstructured, depth: usize) -> Result<Value> {
+    let max_kind = if depth < MAX_DEPTH { SCALARS + 3 } else { SCALARS };
+    let kind = u.int_in_range(0..=max_kind)?;
+    if kind <= SCALARS {
+        return scalar(u, kind);
+    }
+    let len = u.arbitrary_len::<u8>()?;
+    match kind - SCALARS {
+        1 => {
+            let element = u.int_in_range(0..=SCALARS)?;
+            Ok(Value::Array(items(u, len, |u| scalar(u, element))?))
+        }
+        2 => {
+            let element = u.int_in_range(0..=SCALARS)?;
+            Ok(Value::Set(items(u, len, |u| scalar(u, element))?))
+        }
+        _ => {
+            Ok(Value::Tuple(items(u, len, |u| value(u, depth+1))?))
+        }
+    }
+}
+
+/// Generates up to `len` items, collection is cut short when data runs out
+fn items<F>(u: &mut Unstructured, len: usize, mut item: F)
+    -> Result<Vec<Value>>
+    where F: FnMut(&mut Unstructured) -> Result<Value>
+{
+    let mut items = Vec::with_capacity(len);
+    for _ in 0..len {
+        match item(u) {
+            Ok(value) => items.push(value),
+            Err(Error::NotEnoughData) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod test {
+    use arbitrary::Unstructured;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    use super::super::{BigInt, Decimal, Datetime, Value};
+
+    #[test]
+    fn valid_values() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..1000 {
+            let data: Vec<u8> = (0..256).map(|_| rng.gen()).collect();
+            let mut u = Unstructured::new(&data);
+            let big: BigInt = u.arbitrary().unwrap();
+            assert_eq!(big.to_string().parse::<BigInt>().unwrap(), big);
+            let dec: Decimal = u.arbitrary().unwrap();
+            assert!(dec.digits.iter().all(|&d| d < 10000));
+            assert_ne!(dec.digits.last(), Some(&0));
+            let dt: Datetime = u.arbitrary().unwrap();
+            assert!(dt >= Datetime::MIN && dt <= Datetime::MAX);
+            let _: Value = u.arbitrary().unwrap();
+        }
+        let mut u = Unstructured::new(&[]);
+        assert_eq!(u.arbitrary::<Value>().unwrap(), Value::Nothing);
+    }
+}
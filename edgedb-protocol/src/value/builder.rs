@@ -0,0 +1,172 @@
+//! Builders for objects and named tuples
+//!
+//! Shape and field values are kept in sync, so there is no need to build
+//! parallel vectors by hand. Setting a field that already exists replaces
+//! its value and keeps its position.
+use crate::codec::{ObjectShape, ShapeElement};
+use crate::codec::{NamedTupleShape, TupleElement};
+use super::Value;
+
+
+/// Builder of `Value::Object`
+///
+/// ```rust
+/// # use edgedb_protocol::value::{ObjectBuilder, Value};
+/// let user = ObjectBuilder::new()
+///     .field("name", Value::Str("John".into()))
+///     .empty("email")
+///     .link("friends", Value::Set(Vec::new()))
+///     .build();
+/// assert_eq!(user.get_path("name"), Some(&Value::Str("John".into())));
+/// ```
+#[derive(Debug, Default)]
+pub struct ObjectBuilder {
+    elements: Vec<ShapeElement>,
+    fields: Vec<Option<Value>>,
+}
+
+/// Builder of `Value::NamedTuple`
+#[derive(Debug, Default)]
+pub struct NamedTupleBuilder {
+    elements: Vec<TupleElement>,
+    fields: Vec<Value>,
+}
+
+impl ObjectBuilder {
+    pub fn new() -> ObjectBuilder {
+        ObjectBuilder::default()
+    }
+    /// Sets a property
+    pub fn field(self, name: &str, value: Value) -> ObjectBuilder {
+        self.set(name, Some(value), false, false, false)
+    }
+    /// Sets a property to an empty set
+    pub fn empty(self, name: &str) -> ObjectBuilder {
+        self.set(name, None, false, false, false)
+    }
+    /// Sets a link
+    pub fn link(self, name: &str, value: Value) -> ObjectBuilder {
+        self.set(name, Some(value), false, false, true)
+    }
+    /// Sets a link property
+    pub fn link_property(self, name: &str, value: Value) -> ObjectBuilder {
+        self.set(name, Some(value), false, true, false)
+    }
+    /// Sets an implicit field, like `id` or `__tid__`
+    pub fn implicit(self, name: &str, value: Value) -> ObjectBuilder {
+        self.set(name, Some(value), true, false, false)
+    }
+    /// Number of fields set so far
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+    pub fn build(self) -> Value {
+        Value::Object {
+            shape: ObjectShape::new(self.elements),
+            fields: self.fields,
+        }
+    }
+    fn set(mut self, name: &str, value: Option<Value>,
+           flag_implicit: bool, flag_link_property: bool, flag_link: bool)
+        -> ObjectBuilder
+    {
+        let element = ShapeElement {
+            flag_implicit,
+            flag_link_property,
+            flag_link,
+            cardinality: None,
+            name: name.into(),
+        };
+        match self.elements.iter().position(|el| el.name == name) {
+            Some(idx) => {
+                self.elements[idx] = element;
+                self.fields[idx] = value;
+            }
+            None => {
+                self.elements.push(element);
+                self.fields.push(value);
+            }
+        }
+        self
+    }
+}
+
+impl NamedTupleBuilder {
+    pub fn new() -> NamedTupleBuilder {
+        NamedTupleBuilder::default()
+    }
+    pub fn field(mut self, name: &str, value: Value) -> NamedTupleBuilder {
+        match self.elements.iter().position(|el| el.name == name) {
+            Some(idx) => self.fields[idx] = value,
+            None => {
+                self.elements.push(TupleElement { name: name.into() });
+                self.fields.push(value);
+            }
+        }
+        self
+    }
+    /// Number of fields set so far
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+    pub fn build(self) -> Value {
+        Value::NamedTuple {
+            shape: NamedTupleShape::new(self.elements),
+            fields: self.fields,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::{ObjectShape, ShapeElement};
+    use super::super::Value;
+    use super::{ObjectBuilder, NamedTupleBuilder};
+
+    #[test]
+    fn object() {
+        let value = ObjectBuilder::new()
+            .implicit("id", Value::Int64(1))
+            .field("name", Value::Str("John".into()))
+            .empty("email")
+            .field("name", Value::Str("Mary".into()))
+            .build();
+        let element = |name: &str, flag_implicit| ShapeElement {
+            flag_implicit,
+            flag_link_property: false,
+            flag_link: false,
+            cardinality: None,
+            name: name.into(),
+        };
+        assert_eq!(value, Value::Object {
+            shape: ObjectShape::new(vec![
+                element("id", true),
+                element("name", false),
+                element("email", false),
+            ]),
+            fields: vec![
+                Some(Value::Int64(1)),
+                Some(Value::Str("Mary".into())),
+                None,
+            ],
+        });
+    }
+
+    #[test]
+    fn named_tuple() {
+        let value = NamedTupleBuilder::new()
+            .field("a", Value::Int64(1))
+            .field("b", Value::Bool(true))
+            .field("a", Value::Int64(2))
+            .build();
+        assert_eq!(value.field("a"), Some(&Value::Int64(2)));
+        assert_eq!(value.index(1), Some(&Value::Bool(true)));
+        assert_eq!(value.index(2), None);
+    }
+}
@@ -0,0 +1,76 @@
+use std::convert::TryFrom;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use super::{Datetime, Duration};
+
+
+/// Source of current time for `Datetime::now_with` and similar helpers
+///
+/// Application code that takes a `&dyn Clock` instead of calling
+/// `Datetime::now()` directly can be tested with `TestClock`.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Datetime;
+}
+
+/// Clock backed by `SystemTime::now()`
+///
+/// Panics if the system clock is outside of `Datetime::MIN..=MAX`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+/// Clock that only moves when told to
+#[derive(Debug)]
+pub struct TestClock {
+    now: Mutex<Datetime>,
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Datetime {
+        Datetime::try_from(SystemTime::now())
+            .expect("system time is out of range of datetime")
+    }
+}
+
+impl TestClock {
+    pub fn new(now: Datetime) -> TestClock {
+        TestClock { now: Mutex::new(now) }
+    }
+    pub fn set(&self, now: Datetime) {
+        *self.now.lock().expect("clock is not poisoned") = now;
+    }
+    /// Moves the clock forward (or backward for negative durations)
+    ///
+    /// Panics if the result is out of range of `Datetime`
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().expect("clock is not poisoned");
+        *now = *now + duration;
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Datetime {
+        *self.now.lock().expect("clock is not poisoned")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Clock, TestClock};
+    use crate::value::{Datetime, Duration, LocalDate};
+
+    #[test]
+    fn test_clock() {
+        // 2020-01-01T23:59:59Z
+        let clock = TestClock::new(
+            Datetime::from_unix_micros(1577923199_000_000));
+        assert_eq!(clock.now(), clock.now());
+        assert_eq!(LocalDate::today_with(&clock),
+                   LocalDate::from_ymd(2020, 1, 1).unwrap());
+        clock.advance(Duration::from_micros(1_000_000));
+        assert_eq!(Datetime::now_with(&clock),
+                   Datetime::from_unix_micros(1577923200_000_000));
+        assert_eq!(LocalDate::today_with(&clock),
+                   LocalDate::from_ymd(2020, 1, 2).unwrap());
+    }
+}
@@ -0,0 +1,168 @@
+use std::hash::{Hash, Hasher};
+use std::mem::discriminant;
+
+use super::Value;
+
+
+/// Wrapper of `Value` implementing `Eq` and `Hash`
+///
+/// Floats are compared by their bit pattern, except that all NaNs are
+/// equal to each other and `-0.0` is equal to `0.0`. Everything else is
+/// compared the same way as in `PartialEq` for `Value`.
+///
+/// Useful for deduplicating results or using them as map keys.
+#[derive(Clone, Debug)]
+pub struct HashableValue(pub Value);
+
+impl HashableValue {
+    pub fn into_inner(self) -> Value {
+        self.0
+    }
+}
+
+impl From<Value> for HashableValue {
+    fn from(value: Value) -> HashableValue {
+        HashableValue(value)
+    }
+}
+
+impl PartialEq for HashableValue {
+    fn eq(&self, other: &HashableValue) -> bool {
+        value_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for HashableValue {}
+
+impl Hash for HashableValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_value(&self.0, state)
+    }
+}
+
+fn f32_bits(val: f32) -> u32 {
+    if val.is_nan() {
+        std::f32::NAN.to_bits()
+    } else if val == 0.0 {
+        0
+    } else {
+        val.to_bits()
+    }
+}
+
+fn f64_bits(val: f64) -> u64 {
+    if val.is_nan() {
+        std::f64::NAN.to_bits()
+    } else if val == 0.0 {
+        0
+    } else {
+        val.to_bits()
+    }
+}
+
+fn slice_eq(a: &[Value], b: &[Value]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| value_eq(a, b))
+}
+
+fn value_eq(a: &Value, b: &Value) -> bool {
+    use Value::*;
+    match (a, b) {
+        (Float32(a), Float32(b)) => f32_bits(*a) == f32_bits(*b),
+        (Float64(a), Float64(b)) => f64_bits(*a) == f64_bits(*b),
+        (Set(a), Set(b)) => slice_eq(a, b),
+        (Tuple(a), Tuple(b)) => slice_eq(a, b),
+        (Array(a), Array(b)) => slice_eq(a, b),
+        (NamedTuple { shape: sa, fields: fa },
+         NamedTuple { shape: sb, fields: fb })
+        => sa == sb && slice_eq(fa, fb),
+        (Object { shape: sa, fields: fa },
+         Object { shape: sb, fields: fb })
+        => {
+            sa == sb && fa.len() == fb.len() &&
+            fa.iter().zip(fb).all(|pair| match pair {
+                (Some(a), Some(b)) => value_eq(a, b),
+                (None, None) => true,
+                _ => false,
+            })
+        }
+        // the rest don't contain floats
+        (a, b) => a == b,
+    }
+}
+
+fn hash_value<H: Hasher>(value: &Value, state: &mut H) {
+    use Value::*;
+    discriminant(value).hash(state);
+    match value {
+        Nothing => {}
+        Uuid(v) => v.hash(state),
+        Str(v) => v.hash(state),
+        Bytes(v) => v.hash(state),
+        Int16(v) => v.hash(state),
+        Int32(v) => v.hash(state),
+        Int64(v) => v.hash(state),
+        Float32(v) => f32_bits(*v).hash(state),
+        Float64(v) => f64_bits(*v).hash(state),
+        BigInt(v) => v.hash(state),
+        Decimal(v) => v.hash(state),
+        Bool(v) => v.hash(state),
+        Datetime(v) => v.hash(state),
+        LocalDatetime(v) => v.hash(state),
+        LocalDate(v) => v.hash(state),
+        LocalTime(v) => v.hash(state),
+        Duration(v) => v.hash(state),
+        Json(v) => v.hash(state),
+        Set(items) | Tuple(items) | Array(items) => {
+            items.len().hash(state);
+            for item in items {
+                hash_value(item, state);
+            }
+        }
+        Object { shape, fields } => {
+            shape.hash(state);
+            fields.len().hash(state);
+            for field in fields {
+                field.is_some().hash(state);
+                if let Some(value) = field {
+                    hash_value(value, state);
+                }
+            }
+        }
+        NamedTuple { shape, fields } => {
+            shape.hash(state);
+            fields.len().hash(state);
+            for field in fields {
+                hash_value(field, state);
+            }
+        }
+        Enum(v) => v.hash(state),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::super::Value;
+    use super::HashableValue;
+
+    #[test]
+    fn dedup() {
+        let values = vec![
+            Value::Float64(0.0),
+            Value::Float64(-0.0),
+            Value::Float64(std::f64::NAN),
+            Value::Float64(-std::f64::NAN),
+            Value::Array(vec![Value::Float32(1.5), Value::Str("a".into())]),
+            Value::Array(vec![Value::Float32(1.5), Value::Str("a".into())]),
+            Value::Tuple(vec![Value::Float32(1.5), Value::Str("a".into())]),
+            Value::Int64(0),
+        ];
+        let set = values.into_iter().map(HashableValue)
+            .collect::<HashSet<_>>();
+        assert_eq!(set.len(), 5);
+        assert!(set.contains(&HashableValue(Value::Float64(0.0))));
+        assert!(set.contains(&HashableValue(Value::Float64(std::f64::NAN))));
+        assert!(!set.contains(&HashableValue(Value::Float32(0.0))));
+    }
+}
@@ -0,0 +1,470 @@
+use std::cmp::min;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use super::{Datetime, LocalDatetime, LocalDate, LocalTime, Duration};
+use super::{BigInt, Decimal};
+use super::{ParseError, MICROS_PER_DAY, days_from_ymd, days_in_month};
+use super::base10000;
+
+
+fn error(kind: &'static str) -> ParseError {
+    ParseError { kind }
+}
+
+fn tag(s: &str, prefix: char) -> Option<&str> {
+    if s.starts_with(prefix) {
+        Some(&s[prefix.len_utf8()..])
+    } else {
+        None
+    }
+}
+
+fn sign(s: &str) -> (bool, &str) {
+    match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    }
+}
+
+// Takes exactly `n` ascii digits
+fn digits(s: &str, n: usize) -> Option<(u32, &str)> {
+    if s.len() < n || !s.as_bytes()[..n].iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    Some((s[..n].parse().ok()?, &s[n..]))
+}
+
+// Up to six digits of fractional seconds, returned as microseconds
+fn fraction(s: &str) -> Option<(i64, &str)> {
+    let len = s.bytes().take_while(u8::is_ascii_digit).count();
+    if len == 0 || len > 6 {
+        return None;
+    }
+    let value: i64 = s[..len].parse().ok()?;
+    Some((value * 10_i64.pow(6 - len as u32), &s[len..]))
+}
+
+// `YYYY-MM-DD`, returns days since 2000-01-01
+fn parse_date(s: &str) -> Option<(i64, &str)> {
+    let (year, s) = digits(s, 4)?;
+    let s = tag(s, '-')?;
+    let (month, s) = digits(s, 2)?;
+    let s = tag(s, '-')?;
+    let (day, s) = digits(s, 2)?;
+    let year = year as i64;
+    if year < 1 || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    Some((days_from_ymd(year, month, day), s))
+}
+
+// `HH:MM[:SS[.ffffff]]`, returns microseconds since midnight
+fn parse_time(s: &str) -> Option<(i64, &str)> {
+    let (hour, s) = digits(s, 2)?;
+    let s = tag(s, ':')?;
+    let (minute, mut s) = digits(s, 2)?;
+    let mut second = 0;
+    let mut micros = 0;
+    if let Some(rest) = tag(s, ':') {
+        let (sec, rest) = digits(rest, 2)?;
+        second = sec;
+        s = rest;
+        if let Some(rest) = tag(s, '.') {
+            let (frac, rest) = fraction(rest)?;
+            micros = frac;
+            s = rest;
+        }
+    }
+    if hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    let seconds = (hour as i64 * 60 + minute as i64) * 60 + second as i64;
+    Some((seconds * 1000_000 + micros, s))
+}
+
+// `Z`, `+HH`, `+HHMM` or `+HH:MM`, returns offset in microseconds
+fn parse_offset(s: &str) -> Option<(i64, &str)> {
+    if let Some(rest) = tag(s, 'Z').or_else(|| tag(s, 'z')) {
+        return Some((0, rest));
+    }
+    let (negative, s) = match s.as_bytes().first() {
+        Some(b'+') => (false, &s[1..]),
+        Some(b'-') => (true, &s[1..]),
+        _ => return None,
+    };
+    let (hours, s) = digits(s, 2)?;
+    let (minutes, s) = if let Some(rest) = tag(s, ':') {
+        digits(rest, 2)?
+    } else {
+        digits(s, 2).unwrap_or((0, s))
+    };
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    let micros = (hours as i64 * 60 + minutes as i64) * 60_000_000;
+    Some((if negative { -micros } else { micros }, s))
+}
+
+fn parse_date_time(s: &str) -> Option<(i64, &str)> {
+    let (days, s) = parse_date(s)?;
+    let s = match s.as_bytes().first() {
+        Some(b'T') | Some(b't') | Some(b' ') => &s[1..],
+        _ => return None,
+    };
+    let (time, s) = parse_time(s)?;
+    Some((days * MICROS_PER_DAY + time, s))
+}
+
+// Splits `123.456` into integer and fractional parts
+fn decimal(s: &str) -> Option<(&str, &str, &str)> {
+    let int_len = s.bytes().take_while(u8::is_ascii_digit).count();
+    let (int, s) = s.split_at(int_len);
+    let (frac, s) = if let Some(s) = tag(s, '.') {
+        let len = s.bytes().take_while(u8::is_ascii_digit).count();
+        s.split_at(len)
+    } else {
+        ("", s)
+    };
+    if int.is_empty() && frac.is_empty() {
+        return None;
+    }
+    Some((int, frac, s))
+}
+
+// `-123.456e-7`, returns sign, decimal digits and exponent
+fn number(s: &str) -> Option<(bool, Vec<u8>, i64)> {
+    let (negative, s) = sign(s);
+    let (int, frac, s) = decimal(s)?;
+    let exp = if s.is_empty() {
+        0
+    } else {
+        let s = tag(s, 'e').or_else(|| tag(s, 'E'))?;
+        let (exp_negative, s) = sign(s);
+        if s.is_empty() || !s.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let exp: i64 = s.parse().ok()?;
+        if exp_negative { -exp } else { exp }
+    };
+    let digits = int.bytes().chain(frac.bytes()).map(|c| c - b'0').collect();
+    Some((negative, digits, exp.checked_sub(frac.len() as i64)?))
+}
+
+fn scale(int: &str, frac: &str, unit: i64) -> Option<i64> {
+    // digits beyond microsecond precision are truncated anyway
+    let frac = &frac[..min(frac.len(), 12)];
+    let int: i128 = if int.is_empty() { 0 } else { int.parse().ok()? };
+    let mut micros = int.checked_mul(unit as i128)?;
+    if !frac.is_empty() {
+        let num: i128 = frac.parse().ok()?;
+        let frac_micros = num.checked_mul(unit as i128)?
+            / 10_i128.pow(frac.len() as u32);
+        micros = micros.checked_add(frac_micros)?;
+    }
+    i64::try_from(micros).ok()
+}
+
+fn unit_micros(unit: &str) -> Option<i64> {
+    Some(match &unit.to_lowercase()[..] {
+        "us" | "microsecond" | "microseconds" => 1,
+        "ms" | "millisecond" | "milliseconds" => 1000,
+        "s" | "sec" | "secs" | "second" | "seconds" => 1000_000,
+        "m" | "min" | "mins" | "minute" | "minutes" => 60_000_000,
+        "h" | "hr" | "hrs" | "hour" | "hours" => 3600_000_000,
+        _ => return None,
+    })
+}
+
+// `1 hour 30 minutes`, `1h30m`, `-45.5 seconds`
+fn parse_human_duration(mut s: &str) -> Option<i64> {
+    let mut total: i64 = 0;
+    let mut empty = true;
+    loop {
+        s = s.trim_start();
+        if s.is_empty() {
+            break;
+        }
+        let (negative, rest) = sign(s);
+        let (int, frac, rest) = decimal(rest)?;
+        let rest = rest.trim_start();
+        let unit_len = rest.bytes().take_while(u8::is_ascii_alphabetic).count();
+        let (unit, rest) = rest.split_at(unit_len);
+        let value = scale(int, frac, unit_micros(unit)?)?;
+        total = if negative {
+            total.checked_sub(value)?
+        } else {
+            total.checked_add(value)?
+        };
+        empty = false;
+        s = rest;
+    }
+    if empty {
+        return None;
+    }
+    Some(total)
+}
+
+// ISO 8601 duration with time components only: `PT1H30M`, `-PT0.5S`
+fn parse_iso_duration(s: &str) -> Option<i64> {
+    let (negative, s) = sign(s);
+    let s = tag(s, 'P')?;
+    let mut s = tag(s, 'T')?;
+    if s.is_empty() {
+        return None;
+    }
+    let mut total: i64 = 0;
+    while !s.is_empty() {
+        let (int, frac, rest) = decimal(s)?;
+        let unit = match rest.as_bytes().first() {
+            Some(b'H') => 3600_000_000,
+            Some(b'M') => 60_000_000,
+            Some(b'S') => 1000_000,
+            _ => return None,
+        };
+        total = total.checked_add(scale(int, frac, unit)?)?;
+        s = &rest[1..];
+    }
+    if negative {
+        return total.checked_neg();
+    }
+    Some(total)
+}
+
+impl FromStr for Datetime {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Datetime, ParseError> {
+        parse_date_time(s.trim())
+            .and_then(|(micros, s)| {
+                let (offset, s) = parse_offset(s)?;
+                if !s.is_empty() {
+                    return None;
+                }
+                Datetime::try_from_micros(micros - offset).ok()
+            })
+            .ok_or(error("datetime"))
+    }
+}
+
+impl FromStr for LocalDatetime {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<LocalDatetime, ParseError> {
+        match parse_date_time(s.trim()) {
+            Some((micros, "")) => Ok(LocalDatetime { micros }),
+            _ => Err(error("cal::local_datetime")),
+        }
+    }
+}
+
+impl FromStr for LocalDate {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<LocalDate, ParseError> {
+        match parse_date(s.trim()) {
+            Some((days, "")) => Ok(LocalDate { days: days as i32 }),
+            _ => Err(error("cal::local_date")),
+        }
+    }
+}
+
+impl FromStr for LocalTime {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<LocalTime, ParseError> {
+        match parse_time(s.trim()) {
+            Some((micros, "")) => Ok(LocalTime { micros }),
+            _ => Err(error("cal::local_time")),
+        }
+    }
+}
+
+impl FromStr for Duration {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Duration, ParseError> {
+        let s = s.trim();
+        let micros = if sign(s).1.starts_with('P') {
+            parse_iso_duration(s)
+        } else {
+            parse_human_duration(s)
+        };
+        micros.map(Duration::from_micros).ok_or(error("duration"))
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<BigInt, ParseError> {
+        let (negative, mut digits, mut exp) = number(s.trim())
+            .ok_or(error("bigint"))?;
+        // `1.50e1` is still an integer
+        while exp < 0 && digits.last() == Some(&0) {
+            digits.pop();
+            exp += 1;
+        }
+        if exp < 0 && !digits.is_empty() {
+            return Err(error("bigint"));
+        }
+        let (weight, digits) = base10000(&digits, exp.max(0))
+            .map_err(|_| error("bigint"))?;
+        Ok(BigInt { negative, weight, digits }.normalize())
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Decimal, ParseError> {
+        let (negative, digits, exp) = number(s.trim())
+            .ok_or(error("decimal"))?;
+        let (weight, digits) = base10000(&digits, exp)
+            .map_err(|_| error("decimal"))?;
+        let decimal_digits = if exp < 0 {
+            u16::try_from(-exp).map_err(|_| error("decimal"))?
+        } else {
+            0
+        };
+        Ok(Decimal { negative, weight, decimal_digits, digits }.normalize())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::convert::TryFrom;
+    use std::time::{UNIX_EPOCH, Duration as StdDuration};
+    use super::super::{Datetime, LocalDatetime, LocalDate, LocalTime};
+    use super::super::{Duration, BigInt, Decimal};
+
+    #[test]
+    fn datetime() {
+        let epoch = Datetime::try_from(
+            UNIX_EPOCH + StdDuration::new(1577109148, 156903000)).unwrap();
+        assert_eq!("2019-12-23T13:52:28.156903Z".parse::<Datetime>().unwrap(),
+                   epoch);
+        assert_eq!("2019-12-23 15:52:28.156903+02:00".parse::<Datetime>()
+                   .unwrap(), epoch);
+        assert_eq!("2019-12-23T10:22:28.156903-0330".parse::<Datetime>()
+                   .unwrap(), epoch);
+        assert_eq!("2000-01-01T00:00Z".parse::<Datetime>().unwrap(),
+                   Datetime { micros: 0 });
+        assert!("2019-12-23T13:52:28".parse::<Datetime>().is_err());
+        assert!("2019-12-23T13:52:28.1234567Z".parse::<Datetime>().is_err());
+        assert!("2019-12-23T24:00:00Z".parse::<Datetime>().is_err());
+    }
+
+    #[test]
+    fn local_datetime() {
+        assert_eq!("2019-12-23T14:02:59.709949".parse::<LocalDatetime>()
+                   .unwrap(), LocalDatetime { micros: 630424979709949 });
+        assert_eq!("1999-12-31 23:59:59.999999".parse::<LocalDatetime>()
+                   .unwrap(), LocalDatetime { micros: -1 });
+        assert!("2019-12-23T14:02:59Z".parse::<LocalDatetime>().is_err());
+    }
+
+    #[test]
+    fn local_date() {
+        assert_eq!("2019-12-23".parse::<LocalDate>().unwrap(),
+                   LocalDate { days: 7296 });
+        assert_eq!("2020-02-29".parse::<LocalDate>().unwrap(),
+                   LocalDate { days: 7364 });
+        assert_eq!("0001-01-01".parse::<LocalDate>().unwrap(),
+                   LocalDate { days: -730119 });
+        assert!("2019-02-29".parse::<LocalDate>().is_err());
+        assert!("2019-13-01".parse::<LocalDate>().is_err());
+        assert!("0000-01-01".parse::<LocalDate>().is_err());
+        assert!("2019-1-1".parse::<LocalDate>().is_err());
+    }
+
+    #[test]
+    fn local_time() {
+        assert_eq!("14:07:40.392449".parse::<LocalTime>().unwrap(),
+                   LocalTime { micros: 50860392449 });
+        assert_eq!("14:07".parse::<LocalTime>().unwrap(),
+                   LocalTime { micros: 50820000000 });
+        assert_eq!("00:00:00.5".parse::<LocalTime>().unwrap(),
+                   LocalTime { micros: 500000 });
+        assert!("14:60".parse::<LocalTime>().is_err());
+        assert!("14:07:40.".parse::<LocalTime>().is_err());
+    }
+
+    #[test]
+    fn duration() {
+        let hour_and_half = Duration::from_micros(5400_000_000);
+        assert_eq!("1 hour 30 minutes".parse::<Duration>().unwrap(),
+                   hour_and_half);
+        assert_eq!("1h30m".parse::<Duration>().unwrap(), hour_and_half);
+        assert_eq!("PT1H30M".parse::<Duration>().unwrap(), hour_and_half);
+        assert_eq!("-PT0.5S".parse::<Duration>().unwrap(),
+                   Duration::from_micros(-500_000));
+        assert_eq!("-45.5 seconds".parse::<Duration>().unwrap(),
+                   Duration::from_micros(-45_500_000));
+        assert_eq!("1 hour -1 us".parse::<Duration>().unwrap(),
+                   Duration::from_micros(3599_999_999));
+        assert_eq!("12 ms".parse::<Duration>().unwrap(),
+                   Duration::from_micros(12_000));
+        assert!("".parse::<Duration>().is_err());
+        assert!("1 day".parse::<Duration>().is_err());
+        assert!("P1D".parse::<Duration>().is_err());
+        assert!("10".parse::<Duration>().is_err());
+        // i128::MAX / 1000 milliseconds plus a fraction
+        assert!("170141183460469231731687303715884105.999 ms"
+                .parse::<Duration>().is_err());
+    }
+
+    #[test]
+    fn big_int() {
+        assert_eq!("12345678901234567890".parse::<BigInt>().unwrap()
+                   .to_string(), "12345678901234567890");
+        assert_eq!("-42".parse::<BigInt>().unwrap(), BigInt::from(-42));
+        assert_eq!("+1.5e3".parse::<BigInt>().unwrap(), BigInt::from(1500));
+        assert_eq!("1.50E1".parse::<BigInt>().unwrap(), BigInt::from(15));
+        assert_eq!("1e100".parse::<BigInt>().unwrap(), BigInt {
+            negative: false,
+            weight: 25,
+            digits: vec![1],
+        });
+        assert_eq!("-0".parse::<BigInt>().unwrap(), BigInt::from(0));
+        assert!("1.5".parse::<BigInt>().is_err());
+        assert!("1e".parse::<BigInt>().is_err());
+        assert!("".parse::<BigInt>().is_err());
+        assert!("0x10".parse::<BigInt>().is_err());
+    }
+
+    #[test]
+    fn decimal() {
+        assert_eq!("42.00".parse::<Decimal>().unwrap(), Decimal {
+            negative: false,
+            weight: 0,
+            decimal_digits: 2,
+            digits: vec![42],
+        });
+        assert_eq!("-12345678.901234567".parse::<Decimal>().unwrap(),
+                   Decimal {
+                       negative: true,
+                       weight: 1,
+                       decimal_digits: 9,
+                       digits: vec![1234, 5678, 9012, 3456, 7000],
+                   });
+        assert_eq!("1.5e-3".parse::<Decimal>().unwrap().to_string(),
+                   "0.0015");
+        assert_eq!("15E+2".parse::<Decimal>().unwrap().to_string(), "1500");
+        assert_eq!(".5".parse::<Decimal>().unwrap().to_string(), "0.5");
+        assert!("1.2.3".parse::<Decimal>().is_err());
+        assert!("1e99999999999999999999".parse::<Decimal>().is_err());
+        assert!("10e9223372036854775807".parse::<Decimal>().is_err());
+        assert!("10e9223372036854775807".parse::<BigInt>().is_err());
+    }
+
+    #[test]
+    fn scientific() {
+        assert_eq!(format!("{:e}", BigInt::from(1500)), "1.5e3");
+        assert_eq!(format!("{:e}", BigInt::from(-7)), "-7e0");
+        assert_eq!(format!("{:e}", BigInt::from(0)), "0e0");
+        assert_eq!(format!("{:e}", "0.00012".parse::<Decimal>().unwrap()),
+                   "1.2e-4");
+        assert_eq!(format!("{:e}",
+                           "-12345678.901234567".parse::<Decimal>().unwrap()),
+                   "-1.2345678901234567e7");
+        for text in &["1e100", "-3.25e-20", "7e0"] {
+            let dec: Decimal = text.parse().unwrap();
+            assert_eq!(&format!("{:e}", dec), text);
+        }
+    }
+}
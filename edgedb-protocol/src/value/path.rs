@@ -0,0 +1,132 @@
+//! Navigation through nested values
+//!
+//! Paths use the same notation as EdgeQL: `friends[2].name`, where `.name`
+//! selects an object or named tuple field, `.0` selects a tuple element and
+//! `[2]` selects an element of an array, a set or any tuple.
+use super::Value;
+
+
+impl Value {
+    /// Returns a field of an object, a named tuple or a tuple
+    ///
+    /// Tuple elements are selected by their number, as in `.0`. Fields of
+    /// an object that are set to an empty set are reported as `None`.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Object { shape, fields } => {
+                let idx = shape.elements.iter()
+                    .position(|el| el.name == name)?;
+                fields.get(idx)?.as_ref()
+            }
+            Value::NamedTuple { shape, fields } => {
+                let idx = shape.elements.iter()
+                    .position(|el| el.name == name)?;
+                fields.get(idx)
+            }
+            Value::Tuple(items) => items.get(number(name)?),
+            _ => None,
+        }
+    }
+    /// Returns an element of a set, an array or a tuple by position
+    pub fn index(&self, idx: usize) -> Option<&Value> {
+        match self {
+            Value::Set(items) | Value::Array(items) | Value::Tuple(items)
+            | Value::NamedTuple { fields: items, .. }
+            => items.get(idx),
+            _ => None,
+        }
+    }
+    /// Returns a nested value by path like `friends[2].name`
+    ///
+    /// Returns `None` if any step of the path doesn't exist or path is
+    /// malformed. Empty path returns the value itself.
+    pub fn get_path(&self, path: &str) -> Option<&Value> {
+        let mut value = self;
+        let mut rest = path;
+        let mut first = true;
+        while !rest.is_empty() {
+            if rest.starts_with('[') {
+                let end = rest.find(']')?;
+                value = value.index(number(&rest[1..end])?)?;
+                rest = &rest[end+1..];
+            } else {
+                if !first {
+                    if !rest.starts_with('.') {
+                        return None;
+                    }
+                    rest = &rest[1..];
+                }
+                let end = rest.find(|c| c == '.' || c == '[')
+                    .unwrap_or(rest.len());
+                if end == 0 {
+                    return None;
+                }
+                value = value.field(&rest[..end])?;
+                rest = &rest[end..];
+            }
+            first = false;
+        }
+        Some(value)
+    }
+}
+
+fn number(s: &str) -> Option<usize> {
+    if s.is_empty() || !s.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::codec::{ObjectShape, ShapeElement};
+    use super::super::Value;
+
+    fn shape(names: &[&str]) -> ObjectShape {
+        ObjectShape::new(names.iter().map(|name| ShapeElement {
+            flag_implicit: false,
+            flag_link_property: false,
+            flag_link: false,
+            cardinality: None,
+            name: name.to_string(),
+        }).collect())
+    }
+
+    fn user(name: &str, friends: Vec<Value>) -> Value {
+        Value::Object {
+            shape: shape(&["name", "email", "friends"]),
+            fields: vec![
+                Some(Value::Str(name.into())),
+                None,
+                Some(Value::Set(friends)),
+            ],
+        }
+    }
+
+    #[test]
+    fn path() {
+        let value = user("john", vec![
+            user("mary", vec![]),
+            user("bob", vec![]),
+        ]);
+        assert_eq!(value.get_path("name"), Some(&Value::Str("john".into())));
+        assert_eq!(value.get_path("friends[1].name"),
+                   Some(&Value::Str("bob".into())));
+        assert_eq!(value.get_path(""), Some(&value));
+        assert_eq!(value.get_path("email"), None);
+        assert_eq!(value.get_path("friends[2].name"), None);
+        assert_eq!(value.get_path("friends[x]"), None);
+        assert_eq!(value.get_path("friends[0]name"), None);
+        assert_eq!(value.get_path("name.."), None);
+    }
+
+    #[test]
+    fn tuple() {
+        let value = Value::Array(vec![
+            Value::Tuple(vec![Value::Int64(1), Value::Str("a".into())]),
+        ]);
+        assert_eq!(value.get_path("[0].1"), Some(&Value::Str("a".into())));
+        assert_eq!(value.get_path("[0][0]"), Some(&Value::Int64(1)));
+        assert_eq!(value.index(0).and_then(|v| v.field("2")), None);
+    }
+}
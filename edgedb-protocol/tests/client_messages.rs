@@ -4,8 +4,9 @@ use std::error::Error;
 use bytes::{Bytes, BytesMut};
 
 use edgedb_protocol::client_message::{ClientMessage, ClientHandshake};
-use edgedb_protocol::client_message::{ExecuteScript, Execute};
+use edgedb_protocol::client_message::{ExecuteScript, Execute, Execute1};
 use edgedb_protocol::client_message::{Prepare, IoFormat, Cardinality};
+use edgedb_protocol::client_message::Capabilities;
 use edgedb_protocol::client_message::{DescribeStatement, DescribeAspect};
 use edgedb_protocol::client_message::{SaslInitialResponse};
 use edgedb_protocol::client_message::{SaslResponse};
@@ -58,6 +59,26 @@ fn prepare() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn prepare_implicit_limit() -> Result<(), Box<dyn Error>> {
+    let mut msg = Prepare {
+        headers: HashMap::new(),
+        io_format: IoFormat::Binary,
+        expected_cardinality: Cardinality::Many,
+        statement_name: Bytes::new(),
+        command_text: String::from("SELECT User;"),
+    };
+    assert_eq!(msg.implicit_limit(), None);
+    msg.set_implicit_limit(100);
+    assert_eq!(msg.implicit_limit(), Some(100));
+    encoding_eq!(ClientMessage::Prepare(msg.clone()),
+        b"P\0\0\0\x25\0\x01\xff\x01\0\0\0\x03100bm\0\0\0\0\
+          \0\0\0\x0cSELECT User;");
+    msg.set_implicit_limit(0);
+    assert!(msg.headers.is_empty());
+    Ok(())
+}
+
 #[test]
 fn describe_statement() -> Result<(), Box<dyn Error>> {
     encoding_eq!(ClientMessage::DescribeStatement(DescribeStatement {
@@ -133,3 +154,62 @@ fn restore() -> Result<(), Box<dyn Error>> {
     }), b"<\x00\x00\x00\x0C\x00\x00\x00\x01TEST");
     Ok(())
 }
+
+#[test]
+fn execute1() -> Result<(), Box<dyn Error>> {
+    encoding_eq!(ClientMessage::Execute1(Execute1 {
+        headers: HashMap::new(),
+        allowed_capabilities: Capabilities::MODIFICATIONS,
+        compilation_flags: 0,
+        implicit_limit: 0,
+        output_format: IoFormat::Binary,
+        expected_cardinality: Cardinality::One,
+        command_text: String::from("SELECT 1;"),
+        state_typedesc_id: "00000000-0000-0000-0000-000000000000".parse()?,
+        state_data: Bytes::new(),
+        input_typedesc_id: "00000000-0000-0000-0000-0000000000ff".parse()?,
+        output_typedesc_id: "00000000-0000-0000-0000-000000000105".parse()?,
+        arguments: Bytes::new(),
+    }), bconcat!(b"O\0\0\0e\0\0"
+                 b"\0\0\0\0\0\0\0\x01"
+                 b"\0\0\0\0\0\0\0\0"
+                 b"\0\0\0\0\0\0\0\0"
+                 b"bo\0\0\0\x09SELECT 1;"
+                 b"\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0"
+                 b"\0\0\0\0"
+                 b"\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\xff"
+                 b"\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x05"
+                 b"\0\0\0\0"));
+    Ok(())
+}
+
+#[test]
+fn capabilities() -> Result<(), Box<dyn Error>> {
+    use edgedb_protocol::features::ProtocolVersion;
+
+    assert!(ProtocolVersion::new(1, 0).supports_inline_state());
+    assert!(!ProtocolVersion::current().supports_inline_state());
+
+    let msg = ClientMessage::Execute1(Execute1 {
+        headers: HashMap::new(),
+        allowed_capabilities: Capabilities::ALL,
+        compilation_flags: 0,
+        implicit_limit: 0,
+        output_format: IoFormat::Binary,
+        expected_cardinality: Cardinality::Many,
+        command_text: String::from("SELECT 1;"),
+        state_typedesc_id: "00000000-0000-0000-0000-000000000000".parse()?,
+        state_data: Bytes::new(),
+        input_typedesc_id: "00000000-0000-0000-0000-0000000000ff".parse()?,
+        output_typedesc_id: "00000000-0000-0000-0000-000000000105".parse()?,
+        arguments: Bytes::new(),
+    });
+    let mut bytes = BytesMut::new();
+    msg.encode(&mut bytes)?;
+    // unknown bits are preserved
+    assert_eq!(&bytes[7..15], b"\xff\xff\xff\xff\xff\xff\xff\xff");
+    assert_eq!(ClientMessage::decode(&bytes.freeze())?, msg);
+    assert!(!(Capabilities::ALL - Capabilities::DDL)
+            .contains(Capabilities::DDL));
+    Ok(())
+}
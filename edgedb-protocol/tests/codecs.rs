@@ -7,9 +7,10 @@ use std::time::UNIX_EPOCH;
 use bytes::{Bytes, Buf};
 
 use edgedb_protocol::codec::{build_codec, build_input_codec};
+use edgedb_protocol::codec::{build_codec_with_limits, Limits, SetOrder};
 use edgedb_protocol::codec::{Codec, ObjectShape};
-use edgedb_protocol::value::{Value, Duration};
-use edgedb_protocol::value::{LocalDatetime, LocalDate, LocalTime};
+use edgedb_protocol::value::{Value, Duration, Json};
+use edgedb_protocol::value::{Datetime, LocalDatetime, LocalDate, LocalTime};
 use edgedb_protocol::descriptors::{Descriptor, TypePos};
 use edgedb_protocol::descriptors::BaseScalarTypeDescriptor;
 use edgedb_protocol::descriptors::{ObjectShapeDescriptor, ShapeElement};
@@ -220,6 +221,14 @@ fn uuid() -> Result<(), Box<dyn Error>> {
     )?;
     encoding_eq!(&codec, b"I(\xcc\x1e e\x11\xea\x88H{S\xa6\xad\xb3\x83",
                Value::Uuid("4928cc1e-2065-11ea-8848-7b53a6adb383".parse()?));
+
+    // trailing data is left in the buffer
+    let mut cur = Cursor::new(Bytes::from_static(
+        b"I(\xcc\x1e e\x11\xea\x88H{S\xa6\xad\xb3\x83\x01"));
+    assert_eq!(codec.decode(&mut cur)?,
+               Value::Uuid("4928cc1e-2065-11ea-8848-7b53a6adb383".parse()?));
+    assert_eq!(cur.bytes(), b"\x01");
+    assert!(codec.decode(&mut Cursor::new(Bytes::from_static(b"I("))).is_err());
     Ok(())
 }
 
@@ -263,6 +272,7 @@ fn object_codec() -> Result<(), Box<dyn Error>> {
             flag_implicit: true,
             flag_link_property: false,
             flag_link: false,
+            cardinality: None,
             name: String::from("__tid__"),
             type_pos: TypePos(0),
         },
@@ -270,6 +280,7 @@ fn object_codec() -> Result<(), Box<dyn Error>> {
             flag_implicit: false,
             flag_link_property: false,
             flag_link: false,
+            cardinality: None,
             name: String::from("id"),
             type_pos: TypePos(0),
         },
@@ -310,6 +321,7 @@ fn set_codec() -> Result<(), Box<dyn Error>> {
             flag_implicit: true,
             flag_link_property: false,
             flag_link: false,
+            cardinality: None,
             name: "__tid__".into(),
             type_pos: TypePos(0),
         },
@@ -317,6 +329,7 @@ fn set_codec() -> Result<(), Box<dyn Error>> {
             flag_implicit: true,
             flag_link_property: false,
             flag_link: false,
+            cardinality: None,
             name: "id".into(),
             type_pos: TypePos(0),
         },
@@ -324,6 +337,7 @@ fn set_codec() -> Result<(), Box<dyn Error>> {
             flag_implicit: false,
             flag_link_property: false,
             flag_link: false,
+            cardinality: None,
             name: "first_name".into(),
             type_pos: TypePos(1),
         },
@@ -333,6 +347,7 @@ fn set_codec() -> Result<(), Box<dyn Error>> {
             flag_implicit: true,
             flag_link_property: false,
             flag_link: false,
+            cardinality: None,
             name: "__tid__".into(),
             type_pos: TypePos(0),
         },
@@ -340,6 +355,7 @@ fn set_codec() -> Result<(), Box<dyn Error>> {
             flag_implicit: true,
             flag_link_property: false,
             flag_link: false,
+            cardinality: None,
             name: "id".into(),
             type_pos: TypePos(0),
         },
@@ -347,6 +363,7 @@ fn set_codec() -> Result<(), Box<dyn Error>> {
             flag_implicit: false,
             flag_link_property: false,
             flag_link: false,
+            cardinality: None,
             name: "first_name".into(),
             type_pos: TypePos(1),
         },
@@ -354,6 +371,7 @@ fn set_codec() -> Result<(), Box<dyn Error>> {
             flag_implicit: false,
             flag_link_property: false,
             flag_link: true,
+            cardinality: None,
             name: "collegues".into(),
             type_pos: TypePos(3),
         },
@@ -478,6 +496,119 @@ fn bigint() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn bigint_limits() -> Result<(), Box<dyn Error>> {
+    let codec = build_codec_with_limits(Some(TypePos(0)),
+        &[
+            Descriptor::BaseScalar(
+                BaseScalarTypeDescriptor {
+                    id: "00000000-0000-0000-0000-000000000110".parse()?,
+                },
+            ),
+        ],
+        &Limits {
+            max_bigint_digits: 1,
+            max_decimal_digits: 1,
+            ..Limits::default()
+        },
+    )?;
+    assert_eq!(decode(&codec, b"\0\x01\0\0\0\0\0\0\0*")?,
+               Value::BigInt(42.into()));
+    assert!(decode(&codec, b"\0\x02\0\x01\0\0\0\0\0\x03\0\x01").is_err());
+    // length is checked before the data, so no allocation is made
+    assert!(decode(&codec, b"\xff\xff\0\x01\0\0\0\0").is_err());
+    Ok(())
+}
+
+#[test]
+fn set_order() -> Result<(), Box<dyn Error>> {
+    let descriptors = [
+        Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+            id: "00000000-0000-0000-0000-000000000105".parse()?,
+        }),
+        Descriptor::Set(SetDescriptor {
+            id: "afbb389d-aa73-2aae-9310-84a9163cb5ed".parse()?,
+            type_pos: TypePos(0),
+        }),
+    ];
+    let data = bconcat!(b"\0\0\0\x01\0\0\0\0\0\0\0\0\0\0\0\x03\0\0\0\x01"
+        b"\0\0\0\x08\0\0\0\0\0\0\0\x03"
+        b"\0\0\0\x08\0\0\0\0\0\0\0\x01"
+        b"\0\0\0\x08\0\0\0\0\0\0\0\x02");
+    let codec = build_codec(Some(TypePos(1)), &descriptors)?;
+    encoding_eq!(&codec, data, Value::Set(vec![
+        Value::Int64(3), Value::Int64(1), Value::Int64(2),
+    ]));
+    let codec = build_codec_with_limits(Some(TypePos(1)), &descriptors,
+        &Limits { set_order: SetOrder::Sorted, ..Limits::default() })?;
+    assert_eq!(decode(&codec, data)?, Value::Set(vec![
+        Value::Int64(1), Value::Int64(2), Value::Int64(3),
+    ]));
+    Ok(())
+}
+
+#[test]
+fn encode_from_iter() -> Result<(), Box<dyn Error>> {
+    use bytes::BytesMut;
+
+    let descriptors = [
+        Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+            id: "00000000-0000-0000-0000-000000000105".parse()?,
+        }),
+        Descriptor::Array(ArrayTypeDescriptor {
+            id: "5d5a2d1c-0a5b-11ea-9cb0-1bd2b2dc6fc4".parse()?,
+            type_pos: TypePos(0),
+            dimensions: vec![None],
+        }),
+    ];
+    let codec = build_input_codec(Some(TypePos(1)), &descriptors)?;
+    for size in &[0i64, 1, 1000] {
+        let mut expected = BytesMut::new();
+        codec.encode(&mut expected, &Value::Array(
+            (0..*size).map(Value::Int64).collect()))?;
+        let mut buf = BytesMut::new();
+        codec.encode_from_iter(&mut buf, 0..*size)?;
+        assert_eq!(buf, expected);
+    }
+
+    let mut buf = BytesMut::new();
+    assert!(codec.encode_from_iter(&mut buf, vec!["x"]).is_err());
+    let scalar = build_input_codec(Some(TypePos(0)), &descriptors)?;
+    assert!(scalar.encode_from_iter(&mut buf, vec![1i64]).is_err());
+    Ok(())
+}
+
+#[test]
+fn max_depth() -> Result<(), Box<dyn Error>> {
+    let mut descriptors = vec![
+        Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+            id: "00000000-0000-0000-0000-000000000105".parse()?,
+        }),
+    ];
+    for idx in 0..3 {
+        descriptors.push(Descriptor::Array(ArrayTypeDescriptor {
+            id: uuid::Uuid::from_u128(0x1000 + idx),
+            type_pos: TypePos(idx as u16),
+            dimensions: vec![None],
+        }));
+    }
+    let limits = Limits { max_depth: 3, ..Limits::default() };
+    assert!(build_codec_with_limits(Some(TypePos(2)), &descriptors,
+                                    &limits).is_ok());
+    assert!(build_codec_with_limits(Some(TypePos(3)), &descriptors,
+                                    &limits).is_err());
+
+    // self-referencing descriptor fails instead of overflowing the stack
+    let descriptors = [
+        Descriptor::Scalar(ScalarTypeDescriptor {
+            id: "234dc787-2646-11ea-bebd-010d530c06ca".parse()?,
+            base_type_pos: TypePos(0),
+        }),
+    ];
+    assert!(build_codec(Some(TypePos(0)), &descriptors).is_err());
+    Ok(())
+}
+
 #[test]
 #[cfg(feature="bigdecimal")]
 fn decimal() -> Result<(), Box<dyn Error>> {
@@ -530,6 +661,7 @@ fn bool() -> Result<(), Box<dyn Error>> {
 
 #[test]
 fn datetime() -> Result<(), Box<dyn Error>> {
+    use std::convert::TryFrom;
     use std::time::Duration;
     let codec = build_codec(Some(TypePos(0)),
         &[
@@ -540,8 +672,10 @@ fn datetime() -> Result<(), Box<dyn Error>> {
     )?;
 
     encoding_eq!(&codec, b"\0\x02=^\x1bTc\xe7",
-        Value::Datetime(
-            UNIX_EPOCH + Duration::new(1577109148, 156903000)));
+        Value::Datetime(Datetime::try_from(
+            UNIX_EPOCH + Duration::new(1577109148, 156903000))?));
+    assert!(decode(&codec, &i64::MAX.to_be_bytes()).is_err());
+    assert!(decode(&codec, &i64::MIN.to_be_bytes()).is_err());
     Ok(())
 }
 
@@ -557,6 +691,7 @@ fn local_datetime() -> Result<(), Box<dyn Error>> {
 
     encoding_eq!(&codec, b"\0\x02=^@\xf9\x1f\xfd",
         Value::LocalDatetime(LocalDatetime::from_micros(630424979709949)));
+    assert!(decode(&codec, &i64::MAX.to_be_bytes()).is_err());
     Ok(())
 }
 
@@ -601,7 +736,7 @@ fn json() -> Result<(), Box<dyn Error>> {
     )?;
 
     encoding_eq!(&codec, b"\x01\"txt\"",
-        Value::Json(String::from(r#""txt""#)));
+        Value::Json(Json::new_unchecked(String::from(r#""txt""#))));
     Ok(())
 }
 
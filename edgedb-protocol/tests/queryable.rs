@@ -0,0 +1,254 @@
+use std::error::Error;
+use std::io::Cursor;
+
+use bytes::BytesMut;
+
+use edgedb_derive::Queryable;
+use edgedb_protocol::codec::{build_codec, ObjectShape, ShapeElement};
+use edgedb_protocol::descriptors::{Descriptor, TypePos};
+use edgedb_protocol::descriptors::BaseScalarTypeDescriptor;
+use edgedb_protocol::descriptors::ObjectShapeDescriptor;
+use edgedb_protocol::descriptors as desc;
+use edgedb_protocol::codec;
+use edgedb_protocol::queryable::PartialDecoder;
+use edgedb_protocol::value::Value;
+
+
+#[derive(Queryable, Debug, PartialEq)]
+struct User {
+    name: String,
+}
+
+fn element(name: &str, implicit: bool, pos: u16) -> desc::ShapeElement {
+    desc::ShapeElement {
+        flag_implicit: implicit,
+        flag_link_property: false,
+        flag_link: false,
+        cardinality: None,
+        name: name.into(),
+        type_pos: TypePos(pos),
+    }
+}
+
+fn user_descriptors() -> Vec<Descriptor> {
+    vec![
+        Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+            id: codec::STD_UUID,
+        }),
+        Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+            id: codec::STD_STR,
+        }),
+        Descriptor::ObjectShape(ObjectShapeDescriptor {
+            id: "5eeb5b3f-1b43-11ea-9b03-b70ad1df9cbc".parse().unwrap(),
+            elements: vec![
+                element("__tid__", true, 0),
+                element("id", true, 0),
+                element("email", false, 1),
+                element("name", false, 1),
+                element("nickname", false, 1),
+            ],
+        }),
+    ]
+}
+
+fn shape_element(name: &str) -> ShapeElement {
+    ShapeElement {
+        flag_implicit: false,
+        flag_link_property: false,
+        flag_link: false,
+        cardinality: None,
+        name: name.into(),
+    }
+}
+
+#[test]
+fn partial_decode() -> Result<(), Box<dyn Error>> {
+    let descriptors = user_descriptors();
+    let codec = build_codec(Some(TypePos(2)), &descriptors)?;
+    let shape = match &descriptors[2] {
+        Descriptor::ObjectShape(d) => ObjectShape::from(&d.elements[..]),
+        _ => unreachable!(),
+    };
+    let mut buf = BytesMut::new();
+    codec.encode(&mut buf, &Value::Object {
+        shape,
+        fields: vec![
+            Some(Value::Uuid("5eeb5b3f-1b43-11ea-9b03-b70ad1df9cbc".parse()?)),
+            Some(Value::Uuid("6bd7a9b2-1b43-11ea-9b03-4b7ae7e2a7d0".parse()?)),
+            Some(Value::Str("john@example.com".into())),
+            Some(Value::Str("John".into())),
+            None,
+        ],
+    })?;
+
+    let decoder = PartialDecoder::<User>::new(TypePos(2), &descriptors)?;
+    let (user, rest) = decoder.decode(&mut Cursor::new(buf.freeze()))?;
+    assert_eq!(user, User { name: "John".into() });
+    assert_eq!(rest, Value::Object {
+        shape: ObjectShape::new(vec![
+            shape_element("email"),
+            shape_element("nickname"),
+        ]),
+        fields: vec![
+            Some(Value::Str("john@example.com".into())),
+            None,
+        ],
+    });
+    Ok(())
+}
+
+#[test]
+fn partial_missing_field() {
+    #[derive(Queryable, Debug)]
+    struct Account {
+        login: String,
+    }
+
+    let descriptors = user_descriptors();
+    assert!(PartialDecoder::<Account>::new(TypePos(2), &descriptors)
+            .is_err());
+}
+
+#[test]
+fn btree_set() -> Result<(), Box<dyn Error>> {
+    use std::collections::BTreeSet;
+    use bytes::Bytes;
+    use edgedb_protocol::queryable::Queryable;
+
+    let data = Bytes::from_static(
+        b"\0\0\0\x01\0\0\0\0\0\0\0\0\0\0\0\x03\0\0\0\x01\
+          \0\0\0\x08\0\0\0\0\0\0\0\x03\
+          \0\0\0\x08\0\0\0\0\0\0\0\x01\
+          \0\0\0\x08\0\0\0\0\0\0\0\x03");
+    let set: BTreeSet<i64> = Queryable::decode(&mut Cursor::new(data))?;
+    assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![1, 3]);
+
+    let empty = Bytes::from_static(b"\0\0\0\0\0\0\0\0\0\0\0\0");
+    let set: BTreeSet<i64> = Queryable::decode(&mut Cursor::new(empty))?;
+    assert!(set.is_empty());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature="with-serde")]
+fn json() -> Result<(), Box<dyn Error>> {
+    use bytes::Bytes;
+    use edgedb_protocol::queryable::Queryable;
+    use edgedb_protocol::queryable::json::Json;
+
+    let data = Bytes::from_static(b"\x01[1, 2, 3]");
+    let Json(numbers): Json<Vec<i32>> =
+        Queryable::decode(&mut Cursor::new(data))?;
+    assert_eq!(numbers, vec![1, 2, 3]);
+
+    let data = Bytes::from_static(b"\x01{\"a\": 1}");
+    assert!(<Json<Vec<i32>> as Queryable>::decode(
+        &mut Cursor::new(data)).is_err());
+    let data = Bytes::from_static(b"\x02[]");
+    assert!(<Json<Vec<i32>> as Queryable>::decode(
+        &mut Cursor::new(data)).is_err());
+    Ok(())
+}
+
+#[test]
+fn std_duration() -> Result<(), Box<dyn Error>> {
+    use std::time::Duration;
+    use bytes::Bytes;
+    use edgedb_protocol::queryable::Queryable;
+    use edgedb_protocol::queryable::duration::AbsDuration;
+
+    let positive = Bytes::from_static(
+        b"\0\0\0\0\0\x16\xe3\x60\0\0\0\0\0\0\0\0");
+    let negative = Bytes::from_static(
+        b"\xff\xff\xff\xff\xff\xe9\x1c\xa0\0\0\0\0\0\0\0\0");
+    let value: Duration = Queryable::decode(
+        &mut Cursor::new(positive.clone()))?;
+    assert_eq!(value, Duration::from_millis(1500));
+    assert!(<Duration as Queryable>::decode(
+        &mut Cursor::new(negative.clone())).is_err());
+
+    let value: AbsDuration = Queryable::decode(&mut Cursor::new(positive))?;
+    assert_eq!(*value, Duration::from_millis(1500));
+    let value: AbsDuration = Queryable::decode(&mut Cursor::new(negative))?;
+    assert_eq!(*value, Duration::from_millis(1500));
+    Ok(())
+}
+
+#[test]
+fn transparent() -> Result<(), Box<dyn Error>> {
+    use bytes::Bytes;
+    use uuid::Uuid;
+    use edgedb_protocol::queryable::Queryable;
+
+    #[derive(Queryable, Debug, PartialEq)]
+    #[edgedb(transparent)]
+    struct UserId(Uuid);
+
+    #[derive(Queryable, Debug, PartialEq)]
+    #[edgedb(transparent)]
+    struct Email {
+        address: String,
+    }
+
+    let data = Bytes::from_static(
+        b"\x1d\x31\x9e\x60\xa1\x32\x11\xea\x9f\x5c\x8f\x4f\x51\x0d\x2b\x07");
+    let id: UserId = Queryable::decode(&mut Cursor::new(data))?;
+    assert_eq!(id,
+        UserId("1d319e60-a132-11ea-9f5c-8f4f510d2b07".parse()?));
+
+    let data = Bytes::from_static(b"john@example.com");
+    let email: Email = Queryable::decode(&mut Cursor::new(data))?;
+    assert_eq!(email, Email { address: "john@example.com".into() });
+
+    let data = Bytes::from_static(b"\0\0\0\0\0\0\0\x01");
+    assert!(<UserId as Queryable>::decode(&mut Cursor::new(data)).is_err());
+    Ok(())
+}
+
+#[test]
+fn type_suggestion() {
+    let mut descriptors = user_descriptors();
+    descriptors.push(Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+        id: codec::STD_INT64,
+    }));
+    descriptors.push(Descriptor::Tuple(desc::TupleTypeDescriptor {
+        id: "e4e5bfa6-7a2b-4ad5-8cea-12c4b0a45b3c".parse().unwrap(),
+        element_types: vec![TypePos(1), TypePos(3)],
+    }));
+    descriptors.push(Descriptor::Array(desc::ArrayTypeDescriptor {
+        id: "0e5fd6a2-0ad8-4dd7-9f61-9eb4a2fe8a2d".parse().unwrap(),
+        type_pos: TypePos(4),
+        dimensions: vec![None],
+    }));
+    descriptors.push(Descriptor::ObjectShape(ObjectShapeDescriptor {
+        id: "c1b67e12-8d7f-4e1b-9b3f-63b3ef6d0f5a".parse().unwrap(),
+        elements: vec![
+            element("__tid__", true, 0),
+            element("id", true, 0),
+            element("name", false, 5),
+        ],
+    }));
+    let err = PartialDecoder::<User>::new(TypePos(6), &descriptors)
+        .unwrap_err();
+    // arrays and tuples can't be decoded with `Queryable` yet
+    assert_eq!(err.to_string(),
+        "unexpected type array<tuple<std::str, std::int64>>, expected str");
+
+    descriptors.push(Descriptor::Set(desc::SetDescriptor {
+        id: "5d2a4e1b-2c3e-4f52-8c2a-0b6d3f1e7a90".parse().unwrap(),
+        type_pos: TypePos(1),
+    }));
+    descriptors.push(Descriptor::ObjectShape(ObjectShapeDescriptor {
+        id: "8f0c3b7e-6d1a-4b8e-9a5f-2e4d7c9b1a63".parse().unwrap(),
+        elements: vec![
+            element("__tid__", true, 0),
+            element("id", true, 0),
+            element("name", false, 7),
+        ],
+    }));
+    let err = PartialDecoder::<User>::new(TypePos(8), &descriptors)
+        .unwrap_err();
+    assert_eq!(err.to_string(),
+        "unexpected type set<std::str>, expected str \
+         (use BTreeSet<String> to decode it)");
+}
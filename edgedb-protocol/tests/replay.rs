@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use bytes::{Bytes, BytesMut};
+use uuid::Uuid;
+
+use edgedb_protocol::replay::{Replay, ReplayError, client_messages};
+use edgedb_protocol::client_message::{ClientMessage};
+use edgedb_protocol::server_message::{ServerMessage, Data, Cardinality};
+use edgedb_protocol::server_message::{CommandDataDescription};
+use edgedb_protocol::server_message::{ReadyForCommand, TransactionState};
+use edgedb_protocol::value::Value;
+
+
+fn description() -> ServerMessage {
+    ServerMessage::CommandDataDescription(CommandDataDescription {
+        headers: HashMap::new(),
+        result_cardinality: Cardinality::Many,
+        input_typedesc_id: Uuid::from_u128(0xFF),
+        input_typedesc: Bytes::from_static(
+            b"\x04\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\xff\0\0"),
+        output_typedesc_id: Uuid::from_u128(0x105),
+        output_typedesc: Bytes::from_static(
+            b"\x02\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x05"),
+    })
+}
+
+fn data(rows: &[&'static [u8]]) -> ServerMessage {
+    ServerMessage::Data(Data {
+        data: rows.iter().map(|r| Bytes::from_static(r)).collect(),
+    })
+}
+
+fn stream(messages: &[ServerMessage]) -> Result<Bytes, Box<dyn Error>> {
+    let mut buf = BytesMut::new();
+    for msg in messages {
+        msg.encode(&mut buf)?;
+    }
+    Ok(buf.freeze())
+}
+
+#[test]
+fn replay_data() -> Result<(), Box<dyn Error>> {
+    let bytes = stream(&[
+        description(),
+        data(&[b"\0\0\0\0\0\0\0\x01", b"\0\0\0\0\0\0\0\x02"]),
+        ServerMessage::ReadyForCommand(ReadyForCommand {
+            headers: HashMap::new(),
+            transaction_state: TransactionState::NotInTransaction,
+        }),
+    ])?;
+    let events = Replay::new().run(bytes)?;
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0].rows, vec![]);
+    assert_eq!(events[1].rows, vec![Value::Int64(1), Value::Int64(2)]);
+    Ok(())
+}
+
+#[test]
+fn replay_errors() -> Result<(), Box<dyn Error>> {
+    let bytes = stream(&[data(&[b"\0\0\0\0\0\0\0\x01"])])?;
+    match Replay::new().run(bytes) {
+        Err(ReplayError::NoDescription { index: 0 }) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+
+    let bytes = stream(&[description(), data(&[b"\0\0\x01"])])?;
+    match Replay::new().run(bytes) {
+        Err(ReplayError::Row { index: 1, .. }) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+
+    let bytes = stream(&[description()])?;
+    match Replay::new().run(bytes.slice(..bytes.len()-1)) {
+        Err(ReplayError::Message { index: 0, .. }) => {}
+        res => panic!("unexpected result {:?}", res),
+    }
+    Ok(())
+}
+
+#[test]
+fn client_stream() -> Result<(), Box<dyn Error>> {
+    let mut buf = BytesMut::new();
+    ClientMessage::Sync.encode(&mut buf)?;
+    ClientMessage::Terminate.encode(&mut buf)?;
+    assert_eq!(client_messages(buf.freeze())?,
+               vec![ClientMessage::Sync, ClientMessage::Terminate]);
+    Ok(())
+}
@@ -13,6 +13,8 @@ use edgedb_protocol::server_message::{ServerKeyData, ParameterStatus};
 use edgedb_protocol::server_message::{CommandComplete};
 use edgedb_protocol::server_message::{PrepareComplete, Cardinality};
 use edgedb_protocol::server_message::{CommandDataDescription, Data};
+use edgedb_protocol::server_message::{CommandDataDescription1};
+use edgedb_protocol::server_message::{ShapeCardinality, Capabilities};
 use edgedb_protocol::server_message::{Authentication};
 use edgedb_protocol::server_message::{LogMessage, MessageSeverity};
 use edgedb_protocol::server_message::{RestoreReady};
@@ -157,6 +159,47 @@ fn command_data_description() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn command_data_description1() -> Result<(), Box<dyn Error>> {
+    use edgedb_protocol::descriptors::TypePos;
+    use edgedb_protocol::features::ProtocolVersion;
+
+    let message = ServerMessage::CommandDataDescription1(
+        CommandDataDescription1 {
+            headers: HashMap::new(),
+            capabilities: Capabilities::MODIFICATIONS,
+            result_cardinality: ShapeCardinality::AtLeastOne,
+            input_typedesc_id: Uuid::from_u128(0xFF),
+            input_typedesc: Bytes::from_static(
+                b"\x04\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\xff\0\0"),
+            output_typedesc_id: Uuid::from_u128(0x105),
+            output_typedesc: Bytes::from_static(
+                b"\x02\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x05"),
+        });
+    let data = bconcat!(b"T\0\0\0[\0\0"
+                        b"\0\0\0\0\0\0\0\x01M"
+                        b"\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\xff"
+                        b"\0\0\0\x13"
+                        b"\x04\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\xff\0"
+                        b"\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x05"
+                        b"\0\0\0\x11"
+                        b"\x02\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x05");
+    let mut bytes = BytesMut::new();
+    message.encode(&mut bytes)?;
+    assert_eq!(&bytes[..], &data[..]);
+    let proto = ProtocolVersion::new(1, 0);
+    let decoded = ServerMessage::decode_with_version(&data.clone().freeze(),
+                                                     &proto)?;
+    assert_eq!(decoded, message);
+    match decoded {
+        ServerMessage::CommandDataDescription1(desc) => {
+            assert_eq!(desc.output()?.root_pos(), Some(TypePos(0)));
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
 #[test]
 fn data() -> Result<(), Box<dyn Error>> {
     encoding_eq!(ServerMessage::Data(Data {
@@ -209,3 +252,101 @@ fn log_message() -> Result<(), Box<dyn Error>> {
         b"L\0\0\0%<\xf0\0\0\0\0\0\0\x16changing system config\0\0");
     Ok(())
 }
+
+#[test]
+fn system_config() -> Result<(), Box<dyn Error>> {
+    use std::io::Cursor;
+    use edgedb_protocol::server_message::SystemConfig;
+    use edgedb_protocol::value::{Duration, Value};
+
+    let status = ParameterStatus {
+        name: Bytes::from_static(b"system_config"),
+        value: Bytes::copy_from_slice(bconcat!(
+            b"\0\0\0\x4f"
+            b"\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11"
+            b"\x02\0\0\0\0\0\0\0\0\0\0\0\0\0\0\x01\x0e"
+            b"\x01\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11\x11"
+            b"\x11\0\x01\0\0\0\0\x14session_idle_timeout\0\0"
+            b"\0\0\0\x1c"
+            b"\0\0\0\x01\0\0\0\0\0\0\0\x10"
+            b"\0\0\0\0\x03\x93\x87\0\0\0\0\0\0\0\0\0")),
+    };
+    let (desc, data) = status.parse_system_config().unwrap()?;
+    let codec = desc.build_codec()?;
+    let value = codec.decode_value(&mut Cursor::new(data))?;
+    let config = SystemConfig::from_value(&value);
+    assert_eq!(config.session_idle_timeout,
+               Some(Duration::from_micros(60_000_000)));
+    assert_eq!(SystemConfig::from_value(&Value::empty_tuple()),
+               SystemConfig::default());
+    assert!(status.suggested_pool_concurrency().is_none());
+
+    let status = ParameterStatus {
+        name: Bytes::from_static(b"suggested_pool_concurrency"),
+        value: Bytes::from_static(b"10"),
+    };
+    assert_eq!(status.suggested_pool_concurrency(), Some(10));
+    assert!(status.parse_system_config().is_none());
+    Ok(())
+}
+
+#[test]
+fn error_attributes() {
+    use edgedb_protocol::error_response::{FIELD_HINT, FIELD_DETAILS};
+    use edgedb_protocol::error_response::{FIELD_LINE, FIELD_COLUMN};
+    use edgedb_protocol::error_response::{FIELD_POSITION_START, Position};
+
+    let err = ErrorResponse {
+        severity: ErrorSeverity::Error,
+        code: 0x_04_01_01_00,
+        message: String::from("unexpected 'SELEC'"),
+        attributes: map!{
+            FIELD_HINT => Bytes::from_static(b"did you mean SELECT?"),
+            FIELD_DETAILS => Bytes::from_static(b"\xff"),
+            FIELD_POSITION_START => Bytes::from_static(b"7"),
+            FIELD_LINE => Bytes::from_static(b"2"),
+            FIELD_COLUMN => Bytes::from_static(b"x")
+        },
+    };
+    assert_eq!(err.hint(), Some("did you mean SELECT?"));
+    assert_eq!(err.details(), None);
+    assert_eq!(err.server_traceback(), None);
+    assert_eq!(err.position(), Some(Position {
+        start: Some(7),
+        end: None,
+        line: Some(2),
+        column: None,
+    }));
+}
+
+#[test]
+#[cfg(feature="with-serde")]
+fn error_response_json() -> Result<(), Box<dyn Error>> {
+    use edgedb_protocol::error_response::{FIELD_HINT, FIELD_LINE};
+    use edgedb_protocol::error_response::FIELD_SERVER_TRACEBACK;
+
+    let err = ErrorResponse {
+        severity: ErrorSeverity::Error,
+        code: 0x_04_01_01_00,
+        message: String::from("unexpected 'SELEC'"),
+        attributes: map!{
+            FIELD_HINT => Bytes::from_static(b"did you mean SELECT?"),
+            FIELD_LINE => Bytes::from_static(b"1"),
+            FIELD_SERVER_TRACEBACK => Bytes::from_static(b"Traceback")
+        },
+    };
+    assert_eq!(serde_json::to_value(&err)?, serde_json::json!({
+        "code": 0x_04_01_01_00,
+        "name": "EdgeQLSyntaxError",
+        "message": "unexpected 'SELEC'",
+        "hint": "did you mean SELECT?",
+        "details": null,
+        "position": {
+            "start": null,
+            "end": null,
+            "line": 1,
+            "column": null,
+        },
+    }));
+    Ok(())
+}
@@ -97,6 +97,7 @@ fn object() -> Result<(), Box<dyn Error>> {
                         flag_implicit: true,
                         flag_link_property: false,
                         flag_link: false,
+                        cardinality: None,
                         name: String::from("__tid__"),
                         type_pos: TypePos(0),
                     },
@@ -104,6 +105,7 @@ fn object() -> Result<(), Box<dyn Error>> {
                         flag_implicit: true,
                         flag_link_property: false,
                         flag_link: false,
+                        cardinality: None,
                         name: String::from("id"),
                         type_pos: TypePos(0),
                     },
@@ -111,6 +113,7 @@ fn object() -> Result<(), Box<dyn Error>> {
                         flag_implicit: false,
                         flag_link_property: false,
                         flag_link: false,
+                        cardinality: None,
                         name: String::from("title"),
                         type_pos: TypePos(1),
                     }
@@ -119,3 +122,91 @@ fn object() -> Result<(), Box<dyn Error>> {
         ]);
     Ok(())
 }
+
+#[test]
+fn shape_cardinality() -> Result<(), Box<dyn Error>> {
+    use edgedb_protocol::descriptors::ShapeCardinality;
+    use edgedb_protocol::features::ProtocolVersion;
+
+    let proto = ProtocolVersion::new(0, 13);
+    let mut cur = Cursor::new(Bytes::from_static(
+        b"\x01n\xbb\xbe\xda\0P\x14\xfe\x84\xbc\x82\x15@\xb1R\xcd\0\x02\
+          \0\0\0\x01A\0\0\0\x02id\0\0\
+          \0\0\0\x04m\0\0\0\x07friends\0\x01"));
+    let desc = Descriptor::decode_with_version(&mut cur, &proto)?;
+    assert!(cur.bytes() == b"");
+    assert_eq!(desc, Descriptor::ObjectShape(ObjectShapeDescriptor {
+        id: "6ebbbeda-0050-14fe-84bc-821540b152cd".parse()?,
+        elements: vec![
+            ShapeElement {
+                flag_implicit: true,
+                flag_link_property: false,
+                flag_link: false,
+                cardinality: Some(ShapeCardinality::One),
+                name: String::from("id"),
+                type_pos: TypePos(0),
+            },
+            ShapeElement {
+                flag_implicit: false,
+                flag_link_property: false,
+                flag_link: true,
+                cardinality: Some(ShapeCardinality::Many),
+                name: String::from("friends"),
+                type_pos: TypePos(1),
+            },
+        ]
+    }));
+    assert!(!ShapeCardinality::One.is_optional());
+    assert!(ShapeCardinality::Many.is_multi());
+
+    let mut cur = Cursor::new(Bytes::from_static(
+        b"\x01n\xbb\xbe\xda\0P\x14\xfe\x84\xbc\x82\x15@\xb1R\xcd\0\x01\
+          \0\0\0\x01X\0\0\0\x02id\0\0"));
+    assert!(Descriptor::decode_with_version(&mut cur, &proto).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature="with-serde")]
+fn serde_snapshot() -> Result<(), Box<dyn Error>> {
+    use edgedb_protocol::codec::ObjectShape;
+
+    let descriptors = vec![
+        Descriptor::BaseScalar(BaseScalarTypeDescriptor {
+            id: "00000000-0000-0000-0000-000000000100".parse()?,
+        }),
+        Descriptor::ObjectShape(ObjectShapeDescriptor {
+            id: "6ebbbeda-0050-14fe-84bc-821540b152cd".parse()?,
+            elements: vec![
+                ShapeElement {
+                    flag_implicit: true,
+                    flag_link_property: false,
+                    flag_link: false,
+                    cardinality: None,
+                    name: String::from("id"),
+                    type_pos: TypePos(0),
+                },
+            ],
+        }),
+    ];
+    let json = serde_json::to_string(&descriptors)?;
+    assert_eq!(serde_json::from_str::<Vec<Descriptor>>(&json)?,
+               descriptors);
+
+    let shape = match &descriptors[1] {
+        Descriptor::ObjectShape(d) => ObjectShape::from(&d.elements[..]),
+        _ => unreachable!(),
+    };
+    assert_eq!(serde_json::to_value(&shape)?, serde_json::json!({
+        "elements": [{
+            "flag_implicit": true,
+            "flag_link_property": false,
+            "flag_link": false,
+            "cardinality": null,
+            "name": "id",
+        }],
+    }));
+    assert_eq!(serde_json::from_value::<ObjectShape>(
+        serde_json::to_value(&shape)?)?, shape);
+    Ok(())
+}